@@ -1,5 +1,13 @@
 mod gamecell;
+mod producer;
+mod resource_node;
+mod structure;
 mod unit;
+mod wreckage;
 
 pub use gamecell::GameCell;
-pub use unit::Unit;
+pub use producer::Producer;
+pub use resource_node::ResourceNode;
+pub use structure::Structure;
+pub use unit::{Unit, KILL_XP};
+pub use wreckage::Wreckage;
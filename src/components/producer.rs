@@ -0,0 +1,46 @@
+use crate::types::{Race, UnitKind};
+
+/// Lets an entity queue new units, which are completed at the start of the next round.
+#[derive(Clone, Debug)]
+pub struct Producer {
+    race: Race,
+    options: Vec<UnitKind>,
+    queue: Vec<UnitKind>,
+    morphs: bool,
+}
+
+impl Producer {
+    pub fn new(race: Race, options: &[UnitKind]) -> Self {
+        Self {
+            race,
+            options: options.to_vec(),
+            queue: Vec::new(),
+            morphs: false,
+        }
+    }
+
+    pub fn with_morph(mut self, morphs: bool) -> Self {
+        self.morphs = morphs;
+        self
+    }
+
+    pub fn enqueue(&mut self, kind: UnitKind) {
+        self.queue.push(kind);
+    }
+    pub fn take_queue(&mut self) -> Vec<UnitKind> {
+        std::mem::take(&mut self.queue)
+    }
+
+    pub fn race(&self) -> Race {
+        self.race
+    }
+    pub fn options(&self) -> &[UnitKind] {
+        &self.options
+    }
+    pub fn queue(&self) -> &[UnitKind] {
+        &self.queue
+    }
+    pub fn morphs(&self) -> bool {
+        self.morphs
+    }
+}
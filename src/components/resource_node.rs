@@ -0,0 +1,25 @@
+/// A harvestable deposit. Whoever holds the tile at the start of a round collects its yield.
+#[derive(Clone, Debug)]
+pub struct ResourceNode {
+    amount: i32,
+    yield_per_round: i32,
+}
+
+impl ResourceNode {
+    pub fn new(amount: i32, yield_per_round: i32) -> Self {
+        Self {
+            amount,
+            yield_per_round,
+        }
+    }
+
+    pub fn harvest(&mut self) -> i32 {
+        let x = self.yield_per_round.min(self.amount);
+        self.amount -= x;
+        x
+    }
+
+    pub fn depleted(&self) -> bool {
+        self.amount <= 0
+    }
+}
@@ -0,0 +1,159 @@
+use crate::{
+    components::GameCell,
+    types::{DamageType, Layer, Race, StructureKind, GROUND},
+};
+
+/// A static building. Its `GameCell` marks the top-left corner of its footprint.
+#[derive(Clone, Debug)]
+pub struct Structure {
+    kind: StructureKind,
+    race: Race,
+    hp: (i32, i32),
+    armor: i32,
+    footprint: (i32, i32),
+    damage: i32,
+    damage_type: DamageType,
+    num_attacks: (i32, i32),
+    attack_range: i32,
+    targets: &'static [Layer],
+}
+
+impl Structure {
+    pub fn new(kind: StructureKind, hp: i32) -> Self {
+        Self {
+            kind,
+            race: kind.race(),
+            hp: (hp, hp),
+            armor: 0,
+            footprint: (1, 1),
+            damage: 0,
+            damage_type: DamageType::Ranged,
+            num_attacks: (0, 0),
+            attack_range: 0,
+            targets: GROUND,
+        }
+    }
+
+    pub fn with_footprint(mut self, w: i32, h: i32) -> Self {
+        self.footprint = (w, h);
+        self
+    }
+    pub fn with_armor(mut self, x: i32) -> Self {
+        self.armor = x;
+        self
+    }
+    pub fn with_damage_type(mut self, x: DamageType) -> Self {
+        self.damage_type = x;
+        self
+    }
+    pub fn with_attack(mut self, damage: i32, num_attacks: i32, range: i32) -> Self {
+        self.damage = damage;
+        self.num_attacks = (num_attacks, num_attacks);
+        self.attack_range = range;
+        self
+    }
+
+    pub fn with_targets(mut self, layers: &'static [Layer]) -> Self {
+        self.targets = layers;
+        self
+    }
+
+    /// Returns how much hp was actually taken away.
+    pub fn harm(&mut self, x: i32) -> i32 {
+        let hp_loss = self.preview_harm(x);
+        let dealt = hp_loss.min(self.hp.0.max(0));
+        self.hp.0 -= hp_loss;
+        dealt
+    }
+    /// The hp a hit of `x` would take away after armor.
+    pub fn preview_harm(&self, x: i32) -> i32 {
+        if x > 0 {
+            (x - self.armor).max(1)
+        } else {
+            0
+        }
+    }
+    pub fn repair(&mut self, x: i32) {
+        self.hp.0 = (self.hp.0 + x).min(self.hp.1);
+    }
+    pub fn recharge(&mut self) {
+        self.num_attacks.0 = self.num_attacks.1;
+    }
+    pub fn use_attack(&mut self) {
+        self.num_attacks.0 -= 1;
+    }
+
+    pub fn can_target(&self, layer: Layer) -> bool {
+        self.targets.contains(&layer)
+    }
+    /// Whether the tile at `x`, `y` is covered by this structure when anchored at `cell`.
+    pub fn covers(&self, cell: &GameCell, x: i32, y: i32) -> bool {
+        x >= cell.x()
+            && x < cell.x() + self.footprint.0
+            && y >= cell.y()
+            && y < cell.y() + self.footprint.1
+    }
+    /// Tiles from the nearest tile of this structure's footprint to `x`, `y`, counting diagonals
+    /// as one step.
+    pub fn distance(&self, cell: &GameCell, x: i32, y: i32) -> i32 {
+        let dx = (cell.x() - x)
+            .max(x - (cell.x() + self.footprint.0 - 1))
+            .max(0);
+        let dy = (cell.y() - y)
+            .max(y - (cell.y() + self.footprint.1 - 1))
+            .max(0);
+        dx.max(dy)
+    }
+
+    pub fn kind(&self) -> StructureKind {
+        self.kind
+    }
+    pub fn race(&self) -> Race {
+        self.race
+    }
+    pub fn hp(&self) -> i32 {
+        self.hp.0
+    }
+    pub fn max_hp(&self) -> i32 {
+        self.hp.1
+    }
+    pub fn armor(&self) -> i32 {
+        self.armor
+    }
+    pub fn footprint(&self) -> (i32, i32) {
+        self.footprint
+    }
+    pub fn damage(&self) -> i32 {
+        if self.num_attacks.0 > 0 {
+            self.damage
+        } else {
+            0
+        }
+    }
+    pub fn damage_type(&self) -> DamageType {
+        self.damage_type
+    }
+    pub fn attack_range(&self) -> i32 {
+        self.attack_range
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bracket_lib::prelude::RGB;
+
+    use super::*;
+
+    #[test]
+    fn distance_is_measured_from_the_whole_footprint() {
+        let hive = StructureKind::Hive.structure();
+        let cell = GameCell::new(10, 10, 'H', RGB::new());
+
+        assert_eq!(hive.distance(&cell, 10, 10), 0);
+        assert_eq!(hive.distance(&cell, 11, 11), 0);
+        assert_eq!(hive.distance(&cell, 12, 11), 1);
+        assert_eq!(hive.distance(&cell, 9, 9), 1);
+        assert_eq!(hive.distance(&cell, 14, 8), 3);
+        assert_eq!(hive.distance(&cell, 11, 15), 4);
+    }
+}
@@ -1,54 +1,109 @@
-use bracket_lib::prelude::*;
+use crate::{
+    components::GameCell,
+    types::{
+        AttackShape, DamageType, Layer, Race, Stacking, StatusEffect, StatusKind, UnitKind, GROUND,
+    },
+};
 
-use crate::{components::GameCell, types::Race};
+/// Experience needed for each rank after the first, and the name of every rank.
+const RANK_XP: [i32; 3] = [2, 5, 9];
+const RANK_NAMES: [&str; 4] = ["Recruit", "Veteran", "Elite", "Hero"];
+/// Bonus experience for finishing off an enemy.
+pub const KILL_XP: i32 = 2;
 
 #[derive(Clone, Debug)]
 
 pub struct Unit {
+    kind: UnitKind,
     race: Race,
-    hp: i32,
+    hp: (i32, i32),
+    shields: (i32, i32),
+    armor: i32,
     num_moves: (i32, i32),
     move_dist: i32,
     damage: i32,
+    damage_type: DamageType,
     num_attacks: (i32, i32),
     attack_range: i32,
+    attack_shape: AttackShape,
+    /// Damage lost per tile away from the point of impact.
+    falloff: i32,
+    friendly_fire: bool,
+    /// The layers this unit's weapon can hit.
+    targets: &'static [Layer],
     num_interceptors: (i32, i32),
     _num_interceptor_moves: i32,
     lifespan: Option<i32>,
+    /// How many infantry units fit inside, and the ones currently carried off the map.
+    capacity: i32,
+    cargo: Vec<(GameCell, Unit)>,
+    overwatch: bool,
+    xp: i32,
+    effects: Vec<StatusEffect>,
+    /// Remaining cooldown and charges of each catalog ability.
+    ability_uses: Vec<(i32, Option<i32>)>,
 }
 
 impl Unit {
-    pub fn new(race: Race, hp: i32) -> Self {
+    pub fn new(kind: UnitKind, hp: i32) -> Self {
         Self {
-            race,
-            hp,
+            kind,
+            race: kind.race(),
+            hp: (hp, hp),
+            shields: (0, 0),
+            armor: 0,
             num_moves: (1, 1),
             move_dist: 3,
             damage: 1,
+            damage_type: DamageType::Ranged,
             num_attacks: (1, 1),
             attack_range: 3,
+            attack_shape: AttackShape::Single,
+            falloff: 0,
+            friendly_fire: false,
+            targets: GROUND,
             num_interceptors: (0, 0),
             _num_interceptor_moves: 0,
             lifespan: None,
+            capacity: 0,
+            cargo: Vec::new(),
+            overwatch: false,
+            xp: 0,
+            effects: Vec::new(),
+            ability_uses: kind.abilities().iter().map(|a| (0, a.charges)).collect(),
         }
     }
 
     pub fn new_spider() -> Self {
-        Unit::new(Race::Bug, 1).with_attack_range(1)
+        Unit::new(UnitKind::Spider, 1).with_attack_range(1)
     }
 
     pub fn new_war_carrier() -> Self {
         Self {
+            kind: UnitKind::WarCarrier,
             race: Race::Bionic,
-            hp: 6,
+            hp: (6, 6),
+            shields: (2, 2),
+            armor: 1,
             num_moves: (1, 1),
             move_dist: 1,
             damage: 1,
+            damage_type: DamageType::Ranged,
             num_attacks: (0, 0),
             attack_range: 0,
+            attack_shape: AttackShape::Single,
+            falloff: 0,
+            friendly_fire: false,
+            targets: GROUND,
             num_interceptors: (2, 2),
             _num_interceptor_moves: 2,
             lifespan: None,
+            capacity: 0,
+            cargo: Vec::new(),
+            overwatch: false,
+            xp: 0,
+            effects: Vec::new(),
+            ability_uses: Vec::new(),
         }
     }
 
@@ -64,6 +119,10 @@ impl Unit {
         self.damage = x;
         self
     }
+    pub fn with_damage_type(mut self, x: DamageType) -> Self {
+        self.damage_type = x;
+        self
+    }
     pub fn with_num_attacks(mut self, n: i32) -> Self {
         self.num_attacks = (n, n);
         self
@@ -72,6 +131,32 @@ impl Unit {
         self.attack_range = x;
         self
     }
+    pub fn with_shields(mut self, x: i32) -> Self {
+        self.shields = (x, x);
+        self
+    }
+    pub fn with_armor(mut self, x: i32) -> Self {
+        self.armor = x;
+        self
+    }
+    pub fn with_interceptors(mut self, n: i32) -> Self {
+        self.num_interceptors = (n, n);
+        self
+    }
+    pub fn with_splash(mut self, shape: AttackShape, falloff: i32, friendly_fire: bool) -> Self {
+        self.attack_shape = shape;
+        self.falloff = falloff;
+        self.friendly_fire = friendly_fire;
+        self
+    }
+    pub fn with_capacity(mut self, n: i32) -> Self {
+        self.capacity = n;
+        self
+    }
+    pub fn with_targets(mut self, layers: &'static [Layer]) -> Self {
+        self.targets = layers;
+        self
+    }
     pub fn with_lifespan(mut self, x: i32) -> Self {
         self.lifespan = Some(x);
         self
@@ -80,69 +165,365 @@ impl Unit {
     pub fn use_move(&mut self) {
         self.num_moves.0 -= 1;
     }
+    pub fn end_moves(&mut self) {
+        self.num_moves.0 = 0;
+    }
     pub fn use_attack(&mut self) {
         self.num_attacks.0 -= 1;
     }
-    pub fn harm(&mut self, x: i32) {
-        self.hp -= x;
+    pub fn set_overwatch(&mut self, x: bool) {
+        self.overwatch = x;
+    }
+    /// Shields absorb damage first, then armor reduces whatever gets through to at least 1.
+    /// Returns how much shield and hp was actually taken away.
+    pub fn harm(&mut self, x: i32) -> i32 {
+        let (shield_loss, hp_loss) = self.preview_harm(x);
+        let dealt = shield_loss + hp_loss.min(self.hp.0.max(0));
+        self.shields.0 -= shield_loss;
+        self.hp.0 -= hp_loss;
+        dealt
+    }
+    /// The shields and hp a hit of `x` would take away.
+    pub fn preview_harm(&self, x: i32) -> (i32, i32) {
+        let absorbed = x.max(0).min(self.shields.0);
+        let through = x - absorbed;
+        if through > 0 {
+            (absorbed, (through - self.armor()).max(1))
+        } else {
+            (absorbed, 0)
+        }
+    }
+    pub fn heal(&mut self, x: i32) {
+        self.hp.0 = (self.hp.0 + x).min(self.hp.1);
+    }
+    pub fn regenerate_shields(&mut self, x: i32) {
+        self.shields.0 = (self.shields.0 + x).min(self.shields.1);
+    }
+    /// Adds experience, granting a bonus for every rank reached: extra hp, then damage, then
+    /// movement.
+    pub fn gain_xp(&mut self, x: i32) {
+        let before = self.rank();
+        self.xp += x;
+        for rank in before + 1..=self.rank() {
+            match rank {
+                1 => {
+                    self.hp.1 += 1;
+                    self.hp.0 += 1;
+                }
+                2 => self.damage += 1,
+                _ => self.move_dist += 1,
+            }
+        }
+    }
+    pub fn apply_effect(&mut self, effect: StatusEffect) {
+        match self.effects.iter_mut().find(|e| e.kind == effect.kind) {
+            Some(e) => match effect.kind.stacking() {
+                Stacking::Intensity(max) => {
+                    e.stacks = (e.stacks + effect.stacks).min(max);
+                    e.duration = e.duration.max(effect.duration);
+                }
+                Stacking::Refresh => e.duration = e.duration.max(effect.duration),
+            },
+            None => self.effects.push(effect),
+        }
+    }
+    pub fn remove_effect(&mut self, kind: StatusKind) {
+        self.effects.retain(|e| e.kind != kind);
+    }
+    pub fn drain_shields(&mut self) {
+        self.shields.0 = 0;
+    }
+    pub fn spend_ability(&mut self, i: usize) {
+        let cooldown = self.kind.abilities()[i].cooldown;
+        if let Some((cd, charges)) = self.ability_uses.get_mut(i) {
+            *cd = cooldown;
+            if let Some(x) = charges {
+                *x -= 1;
+            }
+        }
+    }
+    /// Triggers every status at the start of the unit's turn and counts their durations down.
+    pub fn tick_effects(&mut self) {
+        for e in self.effects.iter_mut() {
+            match e.kind {
+                StatusKind::Poison => self.hp.0 -= e.stacks,
+                StatusKind::Stun => {
+                    self.num_moves.0 = 0;
+                    self.num_attacks.0 = 0;
+                }
+                _ => (),
+            }
+            e.duration -= 1;
+        }
+        self.effects.retain(|e| e.duration > 0);
     }
     pub fn recharge(&mut self) {
         self.num_moves.0 = self.num_moves.1;
         self.num_attacks.0 = self.num_attacks.1;
         self.num_interceptors.0 = self.num_interceptors.1;
+        for (_, unit) in self.cargo.iter_mut() {
+            unit.recharge();
+        }
+        for (cd, _) in self.ability_uses.iter_mut() {
+            *cd = (*cd - 1).max(0);
+        }
         if let Some(ls) = self.lifespan {
             self.lifespan = Some(ls - 1);
             if ls - 1 <= 0 {
-                self.hp = 0;
+                self.hp.0 = 0;
             }
         }
     }
-    pub fn make_interceptor(&mut self, x: i32, y: i32) -> Option<(GameCell, Unit)> {
+    pub fn board(&mut self, mut cell: GameCell, unit: Unit) {
+        cell.deselect();
+        self.cargo.push((cell, unit));
+    }
+    /// Takes the most recently boarded unit out of the transport.
+    pub fn unload(&mut self) -> Option<(GameCell, Unit)> {
+        self.cargo.pop()
+    }
+    pub fn launch(&mut self, x: i32, y: i32) -> Option<(GameCell, Unit)> {
         if self.num_interceptors.0 > 0 {
             self.num_interceptors.0 -= 1;
-            Some((
-                GameCell::new(x, y, '^', RGB::from_u8(0, 200, 0)),
-                Unit::new(Race::Bionic, 1)
-                    .with_num_moves(2)
-                    .with_num_attacks(2)
-                    .with_attack_range(1)
-                    .with_lifespan(2),
-            ))
+            self.kind.launches().map(|kind| kind.spawn(x, y))
         } else {
             None
         }
     }
 
     pub fn can_move(&self) -> bool {
-        self.num_moves.0 > 0
+        self.num_moves.0 > 0 && self.can_act()
+    }
+    /// Stunned and burrowed units can't move or attack.
+    pub fn can_act(&self) -> bool {
+        !self.has_effect(StatusKind::Stun) && !self.has_effect(StatusKind::Burrowed)
+    }
+    pub fn has_room(&self) -> bool {
+        (self.cargo.len() as i32) < self.capacity
+    }
+    pub fn can_target(&self, layer: Layer) -> bool {
+        self.targets.contains(&layer)
+    }
+    pub fn targetable(&self) -> bool {
+        !self.has_effect(StatusKind::Burrowed)
+    }
+    pub fn ability_uses(&self, i: usize) -> (i32, Option<i32>) {
+        self.ability_uses.get(i).copied().unwrap_or((0, None))
+    }
+    pub fn ability_ready(&self, i: usize) -> bool {
+        let (cd, charges) = self.ability_uses(i);
+        cd <= 0 && charges != Some(0)
+    }
+    pub fn has_effect(&self, kind: StatusKind) -> bool {
+        self.effects.iter().any(|e| e.kind == kind)
+    }
+    pub fn effects(&self) -> &[StatusEffect] {
+        &self.effects
     }
 
+    pub fn kind(&self) -> UnitKind {
+        self.kind
+    }
     pub fn race(&self) -> Race {
         self.race
     }
+    pub fn layer(&self) -> Layer {
+        if self.has_effect(StatusKind::Burrowed) {
+            Layer::Burrowed
+        } else {
+            self.kind.layer()
+        }
+    }
     pub fn hp(&self) -> i32 {
-        self.hp
+        self.hp.0
+    }
+    pub fn max_hp(&self) -> i32 {
+        self.hp.1
+    }
+    pub fn shields(&self) -> i32 {
+        self.shields.0
+    }
+    pub fn armor(&self) -> i32 {
+        if self.has_effect(StatusKind::Shielded) {
+            self.armor + 1
+        } else {
+            self.armor
+        }
     }
     pub fn num_moves(&self) -> (i32, i32) {
         self.num_moves
     }
     pub fn move_dist(&self) -> i32 {
-        self.move_dist
+        let mut x = self.move_dist;
+        if self.has_effect(StatusKind::Slow) {
+            x -= 1;
+        }
+        if self.has_effect(StatusKind::Haste) {
+            x += 1;
+        }
+        x.max(1)
     }
     pub fn num_attacks(&self) -> (i32, i32) {
         self.num_attacks
     }
     pub fn damage(&self) -> i32 {
-        if self.num_attacks.0 > 0 {
+        if self.num_attacks.0 > 0 && self.can_act() {
+            self.damage
+        } else {
+            0
+        }
+    }
+    /// Damage dealt outside of the unit's own attacks, such as counterattacks and reaction fire.
+    pub fn base_damage(&self) -> i32 {
+        if self.can_act() {
             self.damage
         } else {
             0
         }
     }
+    pub fn damage_type(&self) -> DamageType {
+        self.damage_type
+    }
     pub fn attack_range(&self) -> i32 {
         self.attack_range
     }
+    pub fn attack_shape(&self) -> AttackShape {
+        self.attack_shape
+    }
+    pub fn falloff(&self) -> i32 {
+        self.falloff
+    }
+    pub fn friendly_fire(&self) -> bool {
+        self.friendly_fire
+    }
     pub fn num_interceptors(&self) -> (i32, i32) {
         self.num_interceptors
     }
+    pub fn capacity(&self) -> i32 {
+        self.capacity
+    }
+    pub fn cargo(&self) -> &[(GameCell, Unit)] {
+        &self.cargo
+    }
+    pub fn on_overwatch(&self) -> bool {
+        self.overwatch
+    }
+    pub fn xp(&self) -> i32 {
+        self.xp
+    }
+    pub fn rank(&self) -> usize {
+        RANK_XP.iter().filter(|&&x| self.xp >= x).count()
+    }
+    pub fn rank_name(&self) -> &'static str {
+        RANK_NAMES[self.rank()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trooper() -> Unit {
+        Unit::new(UnitKind::Trooper, 5)
+    }
+
+    #[test]
+    fn shields_absorb_before_armor_and_hp() {
+        let unit = trooper().with_shields(2).with_armor(1);
+        assert_eq!(unit.preview_harm(1), (1, 0));
+        assert_eq!(unit.preview_harm(2), (2, 0));
+        assert_eq!(unit.preview_harm(4), (2, 1));
+        assert_eq!(unit.preview_harm(5), (2, 2));
+    }
+
+    #[test]
+    fn armor_leaves_at_least_one_damage() {
+        let unit = trooper().with_armor(3);
+        assert_eq!(unit.preview_harm(0), (0, 0));
+        assert_eq!(unit.preview_harm(1), (0, 1));
+        assert_eq!(unit.preview_harm(5), (0, 2));
+    }
+
+    #[test]
+    fn harm_applies_the_preview_and_reports_what_was_taken() {
+        let mut unit = trooper().with_shields(1);
+        assert_eq!(unit.harm(3), 3);
+        assert_eq!((unit.shields(), unit.hp()), (0, 3));
+        assert_eq!(unit.harm(10), 3);
+        assert_eq!(unit.hp(), -7);
+    }
+
+    #[test]
+    fn ranks_are_reached_at_the_xp_thresholds() {
+        let mut unit = trooper();
+        assert_eq!(unit.rank_name(), "Recruit");
+        unit.gain_xp(1);
+        assert_eq!(unit.rank(), 0);
+        unit.gain_xp(1);
+        assert_eq!(unit.rank_name(), "Veteran");
+        unit.gain_xp(3);
+        assert_eq!(unit.rank_name(), "Elite");
+        unit.gain_xp(4);
+        assert_eq!(unit.rank_name(), "Hero");
+        unit.gain_xp(100);
+        assert_eq!(unit.rank(), 3);
+    }
+
+    #[test]
+    fn each_rank_grants_its_bonus_once() {
+        let base = trooper();
+        let mut unit = trooper();
+        unit.gain_xp(9);
+        assert_eq!(
+            (unit.hp(), unit.max_hp()),
+            (base.hp() + 1, base.max_hp() + 1)
+        );
+        assert_eq!(unit.base_damage(), base.base_damage() + 1);
+        assert_eq!(unit.move_dist(), base.move_dist() + 1);
+
+        unit.gain_xp(5);
+        assert_eq!(unit.move_dist(), base.move_dist() + 1);
+    }
+
+    #[test]
+    fn poison_stacks_up_to_its_cap_and_other_effects_refresh() {
+        let mut unit = trooper();
+        for _ in 0..5 {
+            unit.apply_effect(StatusEffect::new(StatusKind::Poison, 2));
+        }
+        unit.apply_effect(StatusEffect::new(StatusKind::Poison, 1));
+        assert_eq!(
+            unit.effects(),
+            &[StatusEffect {
+                kind: StatusKind::Poison,
+                duration: 2,
+                stacks: 3,
+            }]
+        );
+
+        unit.apply_effect(StatusEffect::new(StatusKind::Slow, 1));
+        unit.apply_effect(StatusEffect::new(StatusKind::Slow, 3));
+        unit.apply_effect(StatusEffect::new(StatusKind::Slow, 2));
+        assert_eq!(unit.effects()[1], StatusEffect::new(StatusKind::Slow, 3));
+    }
+
+    #[test]
+    fn effects_trigger_each_tick_and_expire() {
+        let mut unit = trooper();
+        unit.apply_effect(StatusEffect::new(StatusKind::Poison, 2));
+        unit.apply_effect(StatusEffect::new(StatusKind::Poison, 2));
+        unit.apply_effect(StatusEffect::new(StatusKind::Stun, 1));
+
+        unit.tick_effects();
+        assert_eq!(unit.hp(), 3);
+        assert_eq!((unit.num_moves().0, unit.num_attacks().0), (0, 0));
+        assert!(!unit.has_effect(StatusKind::Stun));
+
+        unit.tick_effects();
+        assert_eq!(unit.hp(), 1);
+        assert!(unit.effects().is_empty());
+
+        unit.tick_effects();
+        assert_eq!(unit.hp(), 1);
+    }
 }
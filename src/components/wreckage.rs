@@ -0,0 +1,3 @@
+/// The remains of a destroyed Bionic unit. Blocks its tile for ground units.
+#[derive(Clone, Debug)]
+pub struct Wreckage;
@@ -0,0 +1,400 @@
+use bracket_lib::prelude::*;
+
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Serialize};
+
+/// Everything a key can do. Each screen reacts to the actions it knows and ignores the rest, so
+/// one key can mean different things on different screens.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Action {
+    Up,
+    Down,
+    Left,
+    Right,
+    /// Backs out of the current mode or screen.
+    Cancel,
+    /// Finishes typing into a field.
+    Confirm,
+    /// Starts the picked scenario or mission, or carries on from the end of one.
+    Start,
+    /// Picks the numbered entry from a list, counting from 1.
+    Choose(usize),
+    OpenSettings,
+    OpenCampaign,
+    OpenEditor,
+    NextScenario,
+    EditSeed,
+    RandomSeed,
+    RestartCampaign,
+    EnterMoveMode,
+    EnterAttackMode,
+    EnterBuildMode,
+    EnterAbilityMode,
+    EnterUnloadMode,
+    EndTurn,
+    /// Selects the next unit of the side whose turn it is that can still move or attack.
+    CycleNextUnit,
+    CenterView,
+    ToggleMinimap,
+    NextRace,
+    TogglePlayer,
+    TerrainBrush,
+    UnitBrush,
+    StructureBrush,
+    NodeBrush,
+    EraseBrush,
+    HoldBrush,
+    GoalBrush,
+    CycleSurvive,
+    CycleDestroy,
+    CycleHoldRounds,
+    ClearMap,
+    Save,
+}
+
+/// Keys that can be named in the settings file.
+const KEY_NAMES: &[(VirtualKeyCode, &str)] = &[
+    (VirtualKeyCode::A, "A"),
+    (VirtualKeyCode::B, "B"),
+    (VirtualKeyCode::C, "C"),
+    (VirtualKeyCode::D, "D"),
+    (VirtualKeyCode::E, "E"),
+    (VirtualKeyCode::F, "F"),
+    (VirtualKeyCode::G, "G"),
+    (VirtualKeyCode::H, "H"),
+    (VirtualKeyCode::I, "I"),
+    (VirtualKeyCode::J, "J"),
+    (VirtualKeyCode::K, "K"),
+    (VirtualKeyCode::L, "L"),
+    (VirtualKeyCode::M, "M"),
+    (VirtualKeyCode::N, "N"),
+    (VirtualKeyCode::O, "O"),
+    (VirtualKeyCode::P, "P"),
+    (VirtualKeyCode::Q, "Q"),
+    (VirtualKeyCode::R, "R"),
+    (VirtualKeyCode::S, "S"),
+    (VirtualKeyCode::T, "T"),
+    (VirtualKeyCode::U, "U"),
+    (VirtualKeyCode::V, "V"),
+    (VirtualKeyCode::W, "W"),
+    (VirtualKeyCode::X, "X"),
+    (VirtualKeyCode::Y, "Y"),
+    (VirtualKeyCode::Z, "Z"),
+    (VirtualKeyCode::Key1, "1"),
+    (VirtualKeyCode::Key2, "2"),
+    (VirtualKeyCode::Key3, "3"),
+    (VirtualKeyCode::Key4, "4"),
+    (VirtualKeyCode::Key5, "5"),
+    (VirtualKeyCode::Key6, "6"),
+    (VirtualKeyCode::Key7, "7"),
+    (VirtualKeyCode::Key8, "8"),
+    (VirtualKeyCode::Key9, "9"),
+    (VirtualKeyCode::Key0, "0"),
+    (VirtualKeyCode::F1, "F1"),
+    (VirtualKeyCode::F2, "F2"),
+    (VirtualKeyCode::F3, "F3"),
+    (VirtualKeyCode::F4, "F4"),
+    (VirtualKeyCode::F5, "F5"),
+    (VirtualKeyCode::F6, "F6"),
+    (VirtualKeyCode::F7, "F7"),
+    (VirtualKeyCode::F8, "F8"),
+    (VirtualKeyCode::F9, "F9"),
+    (VirtualKeyCode::F10, "F10"),
+    (VirtualKeyCode::F11, "F11"),
+    (VirtualKeyCode::F12, "F12"),
+    (VirtualKeyCode::Up, "Up"),
+    (VirtualKeyCode::Down, "Down"),
+    (VirtualKeyCode::Left, "Left"),
+    (VirtualKeyCode::Right, "Right"),
+    (VirtualKeyCode::Space, "Space"),
+    (VirtualKeyCode::Return, "Enter"),
+    (VirtualKeyCode::Escape, "Escape"),
+    (VirtualKeyCode::Tab, "Tab"),
+    (VirtualKeyCode::Back, "Backspace"),
+    (VirtualKeyCode::Delete, "Delete"),
+    (VirtualKeyCode::Insert, "Insert"),
+    (VirtualKeyCode::Home, "Home"),
+    (VirtualKeyCode::End, "End"),
+    (VirtualKeyCode::PageUp, "PageUp"),
+    (VirtualKeyCode::PageDown, "PageDown"),
+    (VirtualKeyCode::Minus, "Minus"),
+    (VirtualKeyCode::Equals, "Equals"),
+    (VirtualKeyCode::Comma, "Comma"),
+    (VirtualKeyCode::Period, "Period"),
+    (VirtualKeyCode::Slash, "Slash"),
+];
+
+/// A key along with the modifiers that have to be held with it, written like `Ctrl+Shift+S`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct KeyBinding {
+    key: VirtualKeyCode,
+    shift: bool,
+    control: bool,
+    alt: bool,
+}
+
+impl KeyBinding {
+    pub fn new(key: VirtualKeyCode) -> Self {
+        Self {
+            key,
+            shift: false,
+            control: false,
+            alt: false,
+        }
+    }
+
+    pub fn with_control(mut self) -> Self {
+        self.control = true;
+        self
+    }
+
+    /// The key pressed this frame along with the modifiers held, if it can be named.
+    pub fn from_key(ctx: &BTerm) -> Option<Self> {
+        let key = ctx
+            .key
+            .filter(|&key| KEY_NAMES.iter().any(|&(k, _)| k == key))?;
+        Some(Self {
+            key,
+            shift: ctx.shift,
+            control: ctx.control,
+            alt: ctx.alt,
+        })
+    }
+
+    /// Whether this is the key pressed this frame, with exactly these modifiers held.
+    fn pressed(&self, ctx: &BTerm) -> bool {
+        ctx.key == Some(self.key)
+            && ctx.shift == self.shift
+            && ctx.control == self.control
+            && ctx.alt == self.alt
+    }
+}
+
+impl TryFrom<String> for KeyBinding {
+    type Error = String;
+
+    fn try_from(text: String) -> Result<Self, String> {
+        let mut parts = text.split('+').collect::<Vec<_>>();
+        let name = parts.pop().unwrap_or_default();
+        let key = KEY_NAMES
+            .iter()
+            .find(|(_, n)| n.eq_ignore_ascii_case(name))
+            .map(|&(key, _)| key)
+            .ok_or_else(|| format!("unknown key {}", name))?;
+        let mut binding = KeyBinding::new(key);
+        for modifier in parts {
+            match modifier.to_ascii_lowercase().as_str() {
+                "shift" => binding.shift = true,
+                "ctrl" => binding.control = true,
+                "alt" => binding.alt = true,
+                _ => return Err(format!("unknown modifier {}", modifier)),
+            }
+        }
+        Ok(binding)
+    }
+}
+
+impl From<KeyBinding> for String {
+    fn from(binding: KeyBinding) -> String {
+        let name = KEY_NAMES
+            .iter()
+            .find(|&&(key, _)| key == binding.key)
+            .map_or("?", |&(_, name)| name);
+        let mut text = String::new();
+        if binding.control {
+            text += "Ctrl+";
+        }
+        if binding.alt {
+            text += "Alt+";
+        }
+        if binding.shift {
+            text += "Shift+";
+        }
+        text + name
+    }
+}
+
+/// Every key bound to each action. Actions missing from the settings file keep their default
+/// keys.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(
+    from = "HashMap<Action, Vec<KeyBinding>>",
+    into = "BTreeMap<Action, Vec<KeyBinding>>"
+)]
+pub struct Keymap(HashMap<Action, Vec<KeyBinding>>);
+
+impl Default for Keymap {
+    fn default() -> Self {
+        use VirtualKeyCode::*;
+
+        let key = |key| vec![KeyBinding::new(key)];
+        let digits = [Key1, Key2, Key3, Key4, Key5, Key6, Key7, Key8, Key9];
+        let mut bindings = vec![
+            (Action::Up, key(Up)),
+            (Action::Down, key(Down)),
+            (Action::Left, key(Left)),
+            (Action::Right, key(Right)),
+            (Action::Cancel, key(Escape)),
+            (Action::Confirm, key(Return)),
+            (Action::Start, key(Space)),
+            (Action::OpenSettings, key(P)),
+            (Action::OpenCampaign, key(C)),
+            (Action::OpenEditor, key(E)),
+            (Action::NextScenario, key(Tab)),
+            (Action::EditSeed, key(S)),
+            (Action::RandomSeed, key(R)),
+            (Action::RestartCampaign, key(N)),
+            (Action::EnterMoveMode, key(M)),
+            (Action::EnterAttackMode, key(A)),
+            (Action::EnterBuildMode, key(B)),
+            (Action::EnterAbilityMode, key(S)),
+            (Action::EnterUnloadMode, key(U)),
+            (Action::EndTurn, key(Return)),
+            (Action::CycleNextUnit, key(Tab)),
+            (Action::CenterView, key(C)),
+            (Action::ToggleMinimap, key(O)),
+            (Action::NextRace, key(Tab)),
+            (Action::TogglePlayer, key(P)),
+            (Action::TerrainBrush, key(T)),
+            (Action::UnitBrush, key(U)),
+            (Action::StructureBrush, key(B)),
+            (Action::NodeBrush, key(N)),
+            (Action::EraseBrush, key(X)),
+            (Action::HoldBrush, key(H)),
+            (Action::GoalBrush, key(G)),
+            (Action::CycleSurvive, key(V)),
+            (Action::CycleDestroy, key(D)),
+            (Action::CycleHoldRounds, key(K)),
+            (Action::ClearMap, key(C)),
+            (
+                Action::Save,
+                vec![KeyBinding::new(S), KeyBinding::new(S).with_control()],
+            ),
+        ];
+        bindings.extend(
+            digits
+                .into_iter()
+                .enumerate()
+                .map(|(i, digit)| (Action::Choose(i + 1), key(digit))),
+        );
+        Keymap(bindings.into_iter().collect())
+    }
+}
+
+impl From<HashMap<Action, Vec<KeyBinding>>> for Keymap {
+    fn from(bindings: HashMap<Action, Vec<KeyBinding>>) -> Self {
+        let mut keymap = Keymap::default();
+        keymap.0.extend(bindings);
+        keymap
+    }
+}
+
+impl From<Keymap> for BTreeMap<Action, Vec<KeyBinding>> {
+    fn from(keymap: Keymap) -> Self {
+        keymap.0.into_iter().collect()
+    }
+}
+
+impl Keymap {
+    /// Every action bound to the key pressed this frame.
+    pub fn actions(&self, ctx: &BTerm) -> Vec<Action> {
+        self.0
+            .iter()
+            .filter(|(_, bindings)| bindings.iter().any(|binding| binding.pressed(ctx)))
+            .map(|(&action, _)| action)
+            .collect()
+    }
+
+    /// Every action that can be bound, in a fixed order.
+    pub fn all(&self) -> Vec<Action> {
+        let mut actions = self.0.keys().copied().collect::<Vec<_>>();
+        actions.sort();
+        actions
+    }
+
+    /// Adds a key to those bound to an action.
+    pub fn bind(&mut self, action: Action, binding: KeyBinding) {
+        let bindings = self.0.entry(action).or_default();
+        if !bindings.contains(&binding) {
+            bindings.push(binding);
+        }
+    }
+
+    /// Removes every key bound to an action.
+    pub fn unbind(&mut self, action: Action) {
+        self.0.insert(action, Vec::new());
+    }
+
+    /// Every key bound to an action, for the settings menu.
+    pub fn names(&self, action: Action) -> String {
+        self.0
+            .get(&action)
+            .map(|bindings| {
+                bindings
+                    .iter()
+                    .map(|&binding| String::from(binding))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            })
+            .unwrap_or_default()
+    }
+
+    /// The first key bound to an action, for showing in hints.
+    pub fn name(&self, action: Action) -> String {
+        self.0
+            .get(&action)
+            .and_then(|bindings| bindings.first())
+            .map_or_else(|| "-".to_string(), |&binding| binding.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn binding_round_trips_through_text() {
+        let binding = KeyBinding::try_from("Ctrl+Shift+S".to_string()).unwrap();
+        assert_eq!(
+            binding,
+            KeyBinding {
+                key: VirtualKeyCode::S,
+                shift: true,
+                control: true,
+                alt: false,
+            }
+        );
+        assert_eq!(String::from(binding), "Ctrl+Shift+S");
+    }
+
+    #[test]
+    fn binding_names_ignore_case() {
+        assert_eq!(
+            KeyBinding::try_from("ctrl+shift+s".to_string()),
+            KeyBinding::try_from("Ctrl+Shift+S".to_string())
+        );
+        assert_eq!(
+            KeyBinding::try_from("ENTER".to_string()),
+            Ok(KeyBinding::new(VirtualKeyCode::Return))
+        );
+    }
+
+    #[test]
+    fn unknown_keys_and_modifiers_are_rejected() {
+        assert!(KeyBinding::try_from("Ctrl+Foo".to_string()).is_err());
+        assert!(KeyBinding::try_from("Hyper+S".to_string()).is_err());
+        assert!(KeyBinding::try_from(String::new()).is_err());
+    }
+
+    #[test]
+    fn keymap_keeps_defaults_for_missing_actions() {
+        let keymap: Keymap = ron::from_str(r#"{EndTurn: ["Ctrl+E", "F5"]}"#).unwrap();
+        assert_eq!(keymap.names(Action::EndTurn), "Ctrl+E, F5");
+        assert_eq!(keymap.names(Action::Cancel), "Escape");
+
+        let text = ron::to_string(&keymap).unwrap();
+        assert_eq!(ron::from_str::<Keymap>(&text).unwrap(), keymap);
+    }
+}
@@ -1,5 +1,10 @@
 mod components;
+mod input;
+mod options;
+mod settings;
 mod state;
 mod types;
 
+pub use options::Options;
+pub use settings::{Settings, Tileset};
 pub use state::State;
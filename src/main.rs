@@ -1,15 +1,42 @@
 use bracket_lib::prelude::*;
 
-use papercraft::State;
+use std::path::Path;
+
+use papercraft::{Options, Settings, State};
 
 fn main() {
-    let ctx = BTermBuilder::simple(80, 40)
-        .unwrap()
-        .with_tile_dimensions(32, 32)
+    let settings = Settings::load();
+    let options = match Options::from_args(&settings) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("{}", message);
+            std::process::exit(2);
+        }
+    };
+    // A missing sheet would stop the window from opening, so the map falls back to text.
+    let tileset = options.tileset.filter(|tileset| {
+        let found = Path::new("resources").join(&tileset.file).exists();
+        if !found {
+            eprintln!("Tileset resources/{} not found, using text", tileset.file);
+        }
+        found
+    });
+    // The sheet only holds sprites, so text goes on a second console in the built-in font.
+    let builder = match &tileset {
+        Some(tileset) => BTermBuilder::new()
+            .with_dimensions(options.width, options.height)
+            .with_font(&tileset.file, tileset.glyph_size, tileset.glyph_size)
+            .with_font("terminal8x8.png", 8, 8)
+            .with_simple_console(options.width, options.height, &tileset.file)
+            .with_sparse_console(options.width, options.height, "terminal8x8.png"),
+        None => BTermBuilder::simple(options.width, options.height).unwrap(),
+    };
+    let ctx = builder
+        .with_tile_dimensions(options.tile_size, options.tile_size)
         .with_title("PaperCraft")
         .build()
         .unwrap();
-    let gs = State::new(80, 40);
+    let gs = State::new(options.width, options.height, settings).with_tiles(tileset.is_some());
 
     main_loop(ctx, gs).unwrap();
 }
@@ -0,0 +1,56 @@
+use std::env;
+
+use crate::{settings::Tileset, Settings};
+
+/// Smallest console the interface still fits on.
+pub(crate) const MIN_SIZE: (u32, u32) = (60, 20);
+
+const USAGE: &str =
+    "Usage: papercraft [--width COLUMNS] [--height ROWS] [--tile-size PIXELS] [--ascii]";
+
+/// Console size, tile size and font for the game window.
+#[derive(Clone, Debug)]
+pub struct Options {
+    pub width: u32,
+    pub height: u32,
+    pub tile_size: u32,
+    /// The map is drawn in text when there is none.
+    pub tileset: Option<Tileset>,
+}
+
+impl Options {
+    /// Reads `--width`, `--height` and `--tile-size` from the command line, and `--ascii` to skip
+    /// the tileset. Anything missing is taken from the settings; sizes below the minimum are
+    /// raised to it.
+    pub fn from_args(settings: &Settings) -> Result<Self, String> {
+        let mut options = Options {
+            width: settings.width,
+            height: settings.height,
+            tile_size: settings.tile_size,
+            tileset: settings.tileset.clone(),
+        };
+        let mut args = env::args().skip(1);
+        while let Some(arg) = args.next() {
+            let target = match arg.as_str() {
+                "--width" => &mut options.width,
+                "--height" => &mut options.height,
+                "--tile-size" => &mut options.tile_size,
+                "--ascii" => {
+                    options.tileset = None;
+                    continue;
+                }
+                "-h" | "--help" => return Err(USAGE.to_string()),
+                _ => return Err(format!("Unknown option {}\n{}", arg, USAGE)),
+            };
+            *target = args
+                .next()
+                .and_then(|value| value.parse().ok())
+                .filter(|&value| value > 0)
+                .ok_or_else(|| format!("{} needs a positive number\n{}", arg, USAGE))?;
+        }
+        options.width = options.width.max(MIN_SIZE.0);
+        options.height = options.height.max(MIN_SIZE.1);
+        options.tile_size = options.tile_size.max(1);
+        Ok(options)
+    }
+}
@@ -0,0 +1,116 @@
+use std::{collections::HashMap, fs, io};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    input::Keymap,
+    types::{Palette, Race},
+};
+
+const SETTINGS_FILE: &str = "settings.ron";
+
+/// Preferences kept between sessions. Settings missing from the file keep their defaults.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Console size in tiles, used at the next launch unless the command line overrides it.
+    pub width: u32,
+    pub height: u32,
+    /// Tile size in pixels, used at the next launch.
+    pub tile_size: u32,
+    /// The End turn button has to be clicked twice.
+    pub confirm_end_turn: bool,
+    /// The colors the races are drawn in.
+    pub palette: Palette,
+    /// Units and structures are drawn on a dim shade of their race's color.
+    pub race_backgrounds: bool,
+    /// Races are also told apart by shape on the minimap and in the info panel.
+    pub race_marks: bool,
+    /// Draws the map with sprites from a font sheet instead of text, from the next launch.
+    pub tileset: Option<Tileset>,
+    /// Multiplies the speed of everything that plays out over time, such as camera scrolling.
+    pub animation_speed: f32,
+    /// Who plays each race. Races missing here are played by a person.
+    pub controllers: HashMap<Race, Controller>,
+    pub keys: Keymap,
+    /// The file could not be read, so saving leaves it alone instead of replacing it with the
+    /// defaults.
+    #[serde(skip)]
+    unreadable: bool,
+}
+
+/// Who plays a race.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Controller {
+    #[default]
+    Human,
+    /// Moves each unit towards the nearest enemy and attacks whatever is in range.
+    Computer,
+    /// Ends each of its turns straight away.
+    Passive,
+}
+
+/// A font sheet in the `resources` folder holding the map's sprites, 16 to a row in the slot the
+/// catalog gives each one: units from slot 0, structures from 32 and terrain from 48, each in
+/// catalog order. Text is still drawn in the built-in font on a console above it.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Tileset {
+    pub file: String,
+    /// Size of one glyph on the sheet, in pixels.
+    pub glyph_size: u32,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            width: 80,
+            height: 40,
+            tile_size: 32,
+            confirm_end_turn: false,
+            palette: Palette::default(),
+            race_backgrounds: false,
+            race_marks: false,
+            tileset: None,
+            animation_speed: 1.0,
+            controllers: HashMap::new(),
+            keys: Keymap::default(),
+            unreadable: false,
+        }
+    }
+}
+
+impl Settings {
+    /// Reads the settings file, or the defaults if there is none. A file that doesn't parse is
+    /// reported and kept as it is.
+    pub fn load() -> Settings {
+        let Ok(text) = fs::read_to_string(SETTINGS_FILE) else {
+            return Settings::default();
+        };
+        ron::from_str(&text).unwrap_or_else(|e| {
+            eprintln!(
+                "Could not read {}, using the defaults: {}",
+                SETTINGS_FILE, e
+            );
+            Settings {
+                unreadable: true,
+                ..Settings::default()
+            }
+        })
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        if self.unreadable {
+            return Err(io::Error::other(format!(
+                "{} could not be read, fix or remove it first",
+                SETTINGS_FILE
+            )));
+        }
+        let text = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(io::Error::other)?;
+        fs::write(SETTINGS_FILE, text)
+    }
+
+    pub fn controller(&self, race: Race) -> Controller {
+        self.controllers.get(&race).copied().unwrap_or_default()
+    }
+}
@@ -0,0 +1,278 @@
+use bracket_lib::prelude::*;
+
+use legion::{IntoQuery, Read, Write};
+
+use crate::{
+    components::{GameCell, Structure, Unit, KILL_XP},
+    state::{combat::in_range, economy},
+    types::{AbilityEffect, DamageType, StatusEffect, StatusKind, Targeting},
+};
+
+use super::State;
+
+/// Burrowing lasts until the unit chooses to surface.
+const BURROW_DURATION: i32 = i32::MAX;
+
+impl State {
+    /// Picks one of the selected unit's abilities. Abilities aimed at the caster fire at once.
+    pub(super) fn select_ability(&mut self, i: usize) {
+        if let Some((_, caster)) = self.caster() {
+            if let Some(ability) = caster.kind().abilities().get(i) {
+                self.ability = i;
+                if ability.targeting == Targeting::Caster {
+                    self.cast();
+                }
+            }
+        }
+    }
+
+    /// The selected unit of the side whose turn it is.
+    fn caster(&self) -> Option<(Point, Unit)> {
+        let mut query = <(Read<GameCell>, Read<Unit>)>::query();
+
+        query
+            .iter(&self.world)
+            .find(|(cell, unit)| cell.selected() && unit.race() == self.turn)
+            .map(|(cell, unit)| (Point::new(cell.x(), cell.y()), unit.clone()))
+    }
+
+    /// Uses the chosen ability on the clicked tile.
+    pub(super) fn use_ability(&mut self) {
+        if let Some((_, caster)) = self.caster() {
+            if let Some(ability) = caster.kind().abilities().get(self.ability) {
+                if ability.targeting != Targeting::Caster {
+                    self.cast();
+                }
+            }
+        }
+    }
+
+    fn cast(&mut self) {
+        let (origin, caster) = match self.caster() {
+            Some(caster) => caster,
+            None => return,
+        };
+        let ability = match caster.kind().abilities().get(self.ability) {
+            Some(&ability) => ability,
+            None => return,
+        };
+        if !caster.ability_ready(self.ability)
+            || !(caster.can_act() || ability.effect == AbilityEffect::Burrow)
+        {
+            return;
+        }
+
+        let target = match ability.targeting {
+            Targeting::Caster => origin,
+            _ => Point::new(self.mouse.x, self.mouse.y),
+        };
+        // Of the units stacked on the tile, only one is affected: the first the ability accepts.
+        let target_unit = match ability.targeting {
+            Targeting::Ally => self.unit_on(target, |u| u.race() == caster.race()),
+            Targeting::Enemy => {
+                self.unit_on(target, |u| u.race() != caster.race() && u.targetable())
+            }
+            _ => None,
+        };
+        let target_entity = target_unit.as_ref().map(|&(e, _)| e);
+        let valid = in_range(origin, target, ability.range)
+            && match ability.targeting {
+                Targeting::Caster => true,
+                Targeting::Ally | Targeting::Enemy => target_unit.is_some(),
+                Targeting::Tile => {
+                    self.in_bounds(target.x, target.y) && !self.occupied(target.x, target.y)
+                }
+                Targeting::Structure => {
+                    matches!(self.structure_at(target), Some(s) if s.race() == caster.race())
+                }
+                Targeting::Area(_) => self.in_bounds(target.x, target.y),
+            };
+        if !valid {
+            return;
+        }
+
+        let radius = match ability.targeting {
+            Targeting::Area(r) => r,
+            _ => 0,
+        };
+        let mut query = <(Write<GameCell>, Write<Unit>)>::query();
+
+        let mut xp = 0;
+        match ability.effect {
+            AbilityEffect::Heal(x) => {
+                for chunk in query.iter_chunks_mut(&mut self.world) {
+                    for (e, (_, unit)) in chunk.into_iter_entities() {
+                        if Some(e) == target_entity {
+                            unit.heal(x);
+                        }
+                    }
+                }
+            }
+            AbilityEffect::Repair(x) => {
+                let mut structure_query = <(Read<GameCell>, Write<Structure>)>::query();
+
+                for (cell, structure) in structure_query.iter_mut(&mut self.world) {
+                    if structure.covers(cell, target.x, target.y) {
+                        structure.repair(x);
+                    }
+                }
+            }
+            AbilityEffect::Burrow => {
+                for (cell, unit) in query.iter_mut(&mut self.world) {
+                    if cell.selected() {
+                        if unit.has_effect(StatusKind::Burrowed) {
+                            unit.remove_effect(StatusKind::Burrowed);
+                        } else {
+                            unit.apply_effect(StatusEffect::new(
+                                StatusKind::Burrowed,
+                                BURROW_DURATION,
+                            ));
+                        }
+                    }
+                }
+            }
+            AbilityEffect::Charge => {
+                let landing = (-1..=1)
+                    .flat_map(|dy| (-1..=1).map(move |dx| Point::new(target.x + dx, target.y + dy)))
+                    .filter(|p| {
+                        (*p == origin || (self.in_bounds(p.x, p.y) && !self.occupied(p.x, p.y)))
+                            && *p != target
+                    })
+                    .min_by_key(|p| (p.x - origin.x).abs().max((p.y - origin.y).abs()));
+                let landing = match landing {
+                    Some(p) => p,
+                    None => return,
+                };
+                let damage = target_unit
+                    .map(|(_, u)| {
+                        u.kind()
+                            .damage_taken(caster.base_damage(), caster.damage_type())
+                    })
+                    .unwrap_or(0);
+                for chunk in query.iter_chunks_mut(&mut self.world) {
+                    for (e, (cell, unit)) in chunk.into_iter_entities() {
+                        if cell.selected() {
+                            cell.move_pos(landing.x, landing.y);
+                        } else if Some(e) == target_entity {
+                            unit.apply_effect(StatusEffect::new(StatusKind::Stun, 1));
+                            xp += unit.harm(damage);
+                            if unit.hp() <= 0 {
+                                xp += KILL_XP;
+                            }
+                        }
+                    }
+                }
+            }
+            AbilityEffect::Emp => {
+                for (cell, unit) in query.iter_mut(&mut self.world) {
+                    if in_range(target, Point::new(cell.x(), cell.y()), radius) {
+                        unit.drain_shields();
+                    }
+                }
+            }
+            AbilityEffect::Blast(x) => {
+                for (cell, unit) in query.iter_mut(&mut self.world) {
+                    if in_range(target, Point::new(cell.x(), cell.y()), radius) && unit.targetable()
+                    {
+                        let dealt = unit.harm(unit.kind().damage_taken(x, DamageType::Explosive));
+                        if unit.race() != caster.race() {
+                            xp += dealt;
+                            if unit.hp() <= 0 {
+                                xp += KILL_XP;
+                            }
+                        }
+                    }
+                }
+            }
+            AbilityEffect::Spawn(kind) => economy::spawn(&mut self.world, kind, target.x, target.y),
+            AbilityEffect::Status(effect) => {
+                for chunk in query.iter_chunks_mut(&mut self.world) {
+                    for (e, (_, unit)) in chunk.into_iter_entities() {
+                        if Some(e) == target_entity {
+                            unit.apply_effect(effect);
+                        }
+                    }
+                }
+            }
+        }
+
+        for (cell, unit) in query.iter_mut(&mut self.world) {
+            if cell.selected() {
+                unit.spend_ability(self.ability);
+                unit.gain_xp(xp);
+            }
+        }
+    }
+
+    fn structure_at(&self, p: Point) -> Option<Structure> {
+        let mut query = <(Read<GameCell>, Read<Structure>)>::query();
+
+        query
+            .iter(&self.world)
+            .find(|(cell, structure)| structure.covers(cell, p.x, p.y))
+            .map(|(_, structure)| structure.clone())
+    }
+
+    /// Outlines the chosen ability's range, shades the area it would hit and lists the
+    /// selected unit's abilities on the bottom row.
+    pub(super) fn print_abilities(&mut self, ctx: &mut BTerm) {
+        let (origin, caster) = match self.caster() {
+            Some(caster) => caster,
+            None => return,
+        };
+        let abilities = caster.kind().abilities();
+
+        if let Some(ability) = abilities.get(self.ability) {
+            if ability.range > 0 {
+                self.outline(
+                    ctx,
+                    origin.x - ability.range - 1,
+                    origin.y - ability.range - 1,
+                    ability.range * 2 + 2,
+                    ability.range * 2 + 2,
+                    RGB::from_u8(200, 0, 200),
+                );
+            }
+            if let Targeting::Area(r) = ability.targeting {
+                if in_range(origin, self.mouse, ability.range) {
+                    for y in self.mouse.y - r..=self.mouse.y + r {
+                        for x in self.mouse.x - r..=self.mouse.x + r {
+                            if self.in_bounds(x, y) {
+                                self.shade(ctx, x, y, RGB::from_u8(90, 0, 90));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        let line = abilities
+            .iter()
+            .enumerate()
+            .map(|(i, ability)| {
+                let (cd, charges) = caster.ability_uses(i);
+                let mut text = format!(
+                    "{}{}:{}",
+                    if i == self.ability { ">" } else { "" },
+                    i + 1,
+                    ability.name
+                );
+                if cd > 0 {
+                    text += &format!(" cd{}", cd);
+                }
+                if let Some(x) = charges {
+                    text += &format!(" x{}", x);
+                }
+                text
+            })
+            .collect::<Vec<_>>()
+            .join("  ");
+        ctx.print_color(
+            0,
+            self.layout.panel.y1,
+            RGB::from_u8(255, 255, 255),
+            RGB::new(),
+            format!("{}: {}", caster.kind().name(), line),
+        );
+    }
+}
@@ -0,0 +1,188 @@
+use bracket_lib::prelude::*;
+
+use legion::{EntityStore, IntoQuery, Read};
+
+use crate::components::{GameCell, Structure, Unit};
+
+use super::{combat::in_range, State};
+
+/// Moves a computer unit may make in one turn, in case it keeps finding somewhere closer.
+const MAX_MOVES: usize = 8;
+
+fn distance(a: Point, b: Point) -> i32 {
+    (a.x - b.x).abs().max((a.y - b.y).abs())
+}
+
+impl State {
+    /// Plays the turn of a computer-controlled race: each unit attacks the nearest enemy in
+    /// range, or closes in on the nearest one and then tries again.
+    pub(super) fn play_computer_turn(&mut self) {
+        let mut query = <(Read<GameCell>, Read<Unit>)>::query();
+
+        let mut units = Vec::new();
+        for chunk in query.iter_chunks(&self.world) {
+            for (e, (_, unit)) in chunk.into_iter_entities() {
+                if unit.race() == self.turn {
+                    units.push(e);
+                }
+            }
+        }
+
+        for e in units {
+            if self.world.entry_ref(e).is_err() {
+                continue;
+            }
+            self.select_only(Some(e));
+            self.computer_attack();
+            self.computer_move();
+            self.computer_attack();
+            self.clear_cells();
+        }
+
+        self.select_only(None);
+    }
+
+    /// Tiles held by the current side's enemies, for the selected unit to aim at or approach.
+    fn enemy_tiles(&self) -> Vec<Point> {
+        let mut query = <(Read<GameCell>, Read<Unit>)>::query();
+        let mut structure_query = <(Read<GameCell>, Read<Structure>)>::query();
+
+        let mut tiles = query
+            .iter(&self.world)
+            .filter(|(_, unit)| unit.race() != self.turn && unit.targetable())
+            .map(|(cell, _)| Point::new(cell.x(), cell.y()))
+            .collect::<Vec<_>>();
+        for (cell, structure) in structure_query.iter(&self.world) {
+            if structure.race() != self.turn {
+                let (w, h) = structure.footprint();
+                for y in cell.y()..cell.y() + h {
+                    for x in cell.x()..cell.x() + w {
+                        tiles.push(Point::new(x, y));
+                    }
+                }
+            }
+        }
+        tiles
+    }
+
+    fn selected_position(&self) -> Option<(Point, Unit)> {
+        let mut query = <(Read<GameCell>, Read<Unit>)>::query();
+
+        query
+            .iter(&self.world)
+            .find(|(cell, _)| cell.selected())
+            .map(|(cell, unit)| (Point::new(cell.x(), cell.y()), unit.clone()))
+    }
+
+    /// Spends the selected unit's attacks on the nearest enemies it can hit.
+    fn computer_attack(&mut self) {
+        let (origin, unit) = match self.selected_position() {
+            Some(selected) => selected,
+            None => return,
+        };
+        let mut targets = self
+            .enemy_tiles()
+            .into_iter()
+            .filter(|&p| in_range(origin, p, unit.attack_range()))
+            .collect::<Vec<_>>();
+        targets.sort_by_key(|&p| distance(origin, p));
+
+        for p in targets {
+            match self.selected_position() {
+                Some((_, unit)) if unit.num_attacks().0 > 0 => (),
+                _ => return,
+            }
+            self.mouse = p;
+            self.attack_units();
+        }
+    }
+
+    /// Moves the selected unit as close as it can get to the nearest enemy.
+    fn computer_move(&mut self) {
+        for _ in 0..MAX_MOVES {
+            let mut query = <(Read<GameCell>, Read<Unit>)>::query();
+
+            let enemies = self.enemy_tiles();
+            let closest = |p: Point| enemies.iter().map(|&q| distance(p, q)).min();
+            let step = match query
+                .iter(&self.world)
+                .find(|(cell, unit)| cell.selected() && unit.can_move())
+            {
+                Some((cell, unit)) => {
+                    let here = closest(Point::new(cell.x(), cell.y()));
+                    self.reachable(cell, unit)
+                        .keys()
+                        .map(|&(x, y)| Point::new(x, y))
+                        .filter(|&p| closest(p) < here)
+                        .min_by_key(|&p| (closest(p), p.y, p.x))
+                }
+                None => None,
+            };
+            match step {
+                Some(p) => {
+                    self.mouse = p;
+                    self.move_cells();
+                }
+                None => return,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        settings::Settings,
+        state::economy,
+        types::{Race, UnitKind},
+    };
+
+    use super::*;
+
+    fn units(state: &State) -> Vec<(Point, UnitKind, i32)> {
+        let mut query = <(Read<GameCell>, Read<Unit>)>::query();
+
+        query
+            .iter(&state.world)
+            .map(|(cell, unit)| (Point::new(cell.x(), cell.y()), unit.kind(), unit.hp()))
+            .collect()
+    }
+
+    fn state() -> State {
+        let mut state = State::new(40, 20, Settings::default());
+        state.players = vec![Race::Human, Race::Bug];
+        state.turn = Race::Human;
+        state
+    }
+
+    #[test]
+    fn units_close_in_on_the_nearest_enemy() {
+        let mut state = state();
+        economy::spawn(&mut state.world, UnitKind::Trooper, 2, 10);
+        economy::spawn(&mut state.world, UnitKind::Queen, 30, 10);
+
+        state.play_computer_turn();
+
+        let (p, _, _) = units(&state)
+            .into_iter()
+            .find(|(_, kind, _)| *kind == UnitKind::Trooper)
+            .unwrap();
+        assert!(distance(p, Point::new(30, 10)) < 28);
+        assert!(!state.selected);
+    }
+
+    #[test]
+    fn units_attack_enemies_in_range() {
+        let mut state = state();
+        economy::spawn(&mut state.world, UnitKind::Trooper, 10, 10);
+        economy::spawn(&mut state.world, UnitKind::Queen, 12, 10);
+
+        state.play_computer_turn();
+
+        let (_, _, hp) = units(&state)
+            .into_iter()
+            .find(|(_, kind, _)| *kind == UnitKind::Queen)
+            .unwrap();
+        assert!(hp < UnitKind::Queen.unit().hp());
+    }
+}
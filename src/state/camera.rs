@@ -0,0 +1,210 @@
+use bracket_lib::prelude::*;
+
+use legion::{IntoQuery, Read};
+
+use crate::{
+    components::{GameCell, Structure, Unit},
+    input::Action,
+};
+
+use super::{State, MAP_CONSOLE, TEXT_CONSOLE};
+
+/// Milliseconds between steps while an arrow key is held or the mouse rests on the view's edge.
+const SCROLL_DELAY: f32 = 60.0;
+
+impl State {
+    /// Tiles of the world shown at once.
+    pub(super) fn view_size(&self) -> (i32, i32) {
+        (self.layout.map.width(), self.layout.map.height())
+    }
+
+    /// Where a world tile is drawn, or `None` if it is scrolled out of view.
+    pub(super) fn to_screen(&self, x: i32, y: i32) -> Option<Point> {
+        let (w, h) = self.view_size();
+        let (sx, sy) = (x - self.camera.x, y - self.camera.y);
+        (sx >= 0 && sx < w && sy >= 0 && sy < h)
+            .then(|| Point::new(sx + self.layout.map.x1, sy + self.layout.map.y1))
+    }
+
+    /// The world tile under a screen position, or `None` outside the map view.
+    fn to_world(&self, p: Point) -> Option<Point> {
+        let (w, h) = self.view_size();
+        let (vx, vy) = (p.x - self.layout.map.x1, p.y - self.layout.map.y1);
+        (vx >= 0 && vx < w && vy >= 0 && vy < h)
+            .then(|| Point::new(vx + self.camera.x, vy + self.camera.y))
+    }
+
+    /// Moves the view, keeping it over the map.
+    fn scroll(&mut self, dx: i32, dy: i32) {
+        let (w, h) = self.view_size();
+        self.camera.x = (self.camera.x + dx).clamp(0, (self.terrain.width() - w).max(0));
+        self.camera.y = (self.camera.y + dy).clamp(0, (self.terrain.height() - h).max(0));
+    }
+
+    pub(super) fn center_on(&mut self, x: i32, y: i32) {
+        let (w, h) = self.view_size();
+        self.camera = Point::new(x - w / 2, y - h / 2);
+        self.scroll(0, 0);
+    }
+
+    /// Centers the view on the selected unit or structure, or on the side whose turn it is if
+    /// nothing is selected.
+    pub(super) fn center_on_selection(&mut self) {
+        let mut query = <(Read<GameCell>,)>::query();
+
+        match query
+            .iter(&self.world)
+            .find(|(cell,)| cell.selected())
+            .map(|(cell,)| (cell.x(), cell.y()))
+        {
+            Some((x, y)) => self.center_on(x, y),
+            None => self.center_on_turn(),
+        }
+    }
+
+    /// Centers the view on the middle of the side whose turn it is.
+    pub(super) fn center_on_turn(&mut self) {
+        let mut query = <(Read<GameCell>, Read<Unit>)>::query();
+        let mut structure_query = <(Read<GameCell>, Read<Structure>)>::query();
+
+        let mut points = query
+            .iter(&self.world)
+            .filter(|(_, unit)| unit.race() == self.turn)
+            .map(|(cell, _)| (cell.x(), cell.y()))
+            .collect::<Vec<_>>();
+        if points.is_empty() {
+            points = structure_query
+                .iter(&self.world)
+                .filter(|(_, structure)| structure.race() == self.turn)
+                .map(|(cell, _)| (cell.x(), cell.y()))
+                .collect();
+        }
+        let n = points.len() as i32;
+        if n > 0 {
+            self.center_on(
+                points.iter().map(|(x, _)| x).sum::<i32>() / n,
+                points.iter().map(|(_, y)| y).sum::<i32>() / n,
+            );
+        }
+    }
+
+    /// Reads the pointer, and scrolls with the arrow keys, when the pointer rests on the edge
+    /// of the map view, or to wherever the minimap is clicked or dragged. Clicks on the minimap
+    /// go no further.
+    pub(super) fn update_camera(&mut self, ctx: &mut BTerm) {
+        self.pointer = ctx.mouse_point();
+        let actions = self.settings.keys.actions(ctx);
+
+        if actions.contains(&Action::ToggleMinimap) {
+            self.minimap = !self.minimap;
+        }
+        if let Some(p) = self.minimap_to_world(self.pointer) {
+            if self.mouse_pressed || self.mouse_released {
+                self.center_on(p.x, p.y);
+            }
+            self.mouse_released = false;
+            self.mouse = Point::new(-1, -1);
+            return;
+        }
+
+        let (w, h) = self.view_size();
+        let mut step = actions
+            .iter()
+            .find_map(|action| match action {
+                Action::Left => Some((-1, 0)),
+                Action::Right => Some((1, 0)),
+                Action::Up => Some((0, -1)),
+                Action::Down => Some((0, 1)),
+                _ => None,
+            })
+            .unwrap_or((0, 0));
+        if step == (0, 0) {
+            if let Some(p) = self.to_world(self.pointer) {
+                let (vx, vy) = (p.x - self.camera.x, p.y - self.camera.y);
+                step.0 = (vx == w - 1) as i32 - (vx == 0) as i32;
+                step.1 = (vy == h - 1) as i32 - (vy == 0) as i32;
+            }
+            self.scroll_timer += ctx.frame_time_ms;
+            if step != (0, 0)
+                && self.scroll_timer >= SCROLL_DELAY / self.settings.animation_speed.max(0.1)
+            {
+                self.scroll_timer = 0.0;
+                self.scroll(step.0, step.1);
+            }
+        } else {
+            self.scroll(step.0, step.1);
+        }
+
+        self.mouse = self
+            .to_world(self.pointer)
+            .unwrap_or_else(|| Point::new(-1, -1));
+    }
+
+    /// Prints at a world tile, if it is in view.
+    pub(super) fn print_at(
+        &self,
+        ctx: &mut BTerm,
+        x: i32,
+        y: i32,
+        fg: RGB,
+        bg: RGB,
+        text: impl ToString,
+    ) {
+        if let Some(p) = self.to_screen(x, y) {
+            ctx.print_color(p.x, p.y, fg, bg, text.to_string());
+        }
+    }
+
+    /// Draws a single glyph at a world tile, if it is in view.
+    pub(super) fn set_at(
+        &self,
+        ctx: &mut BTerm,
+        x: i32,
+        y: i32,
+        fg: RGB,
+        bg: RGB,
+        glyph: FontCharType,
+    ) {
+        if let Some(p) = self.to_screen(x, y) {
+            self.on_map_console(ctx, |ctx| ctx.set(p.x, p.y, fg, bg, glyph));
+        }
+    }
+
+    /// Sets the background of a world tile, if it is in view.
+    pub(super) fn shade(&self, ctx: &mut BTerm, x: i32, y: i32, bg: RGB) {
+        if let Some(p) = self.to_screen(x, y) {
+            self.on_map_console(ctx, |ctx| ctx.set_bg(p.x, p.y, bg));
+        }
+    }
+
+    /// Draws on the console holding the map's sprites, then goes back to the text console.
+    fn on_map_console(&self, ctx: &mut BTerm, draw: impl FnOnce(&mut BTerm)) {
+        if self.tiles {
+            ctx.set_active_console(MAP_CONSOLE);
+            draw(ctx);
+            ctx.set_active_console(TEXT_CONSOLE);
+        } else {
+            draw(ctx);
+        }
+    }
+
+    /// Draws the part of a hollow box, given in world tiles, that is in view.
+    pub(super) fn outline(&self, ctx: &mut BTerm, x: i32, y: i32, w: i32, h: i32, fg: RGB) {
+        for i in 0..=w {
+            for j in 0..=h {
+                let glyph = match (i == 0, i == w, j == 0, j == h) {
+                    (true, _, true, _) => '┌',
+                    (_, true, true, _) => '┐',
+                    (true, _, _, true) => '└',
+                    (_, true, _, true) => '┘',
+                    (_, _, true, _) | (_, _, _, true) => '─',
+                    (true, _, _, _) | (_, true, _, _) => '│',
+                    _ => continue,
+                };
+                if let Some(p) = self.to_screen(x + i, y + j) {
+                    ctx.set(p.x, p.y, fg, RGB::new(), to_cp437(glyph));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,203 @@
+use bracket_lib::prelude::*;
+
+use legion::{IntoQuery, Read};
+
+use crate::{
+    components::Unit,
+    input::Action,
+    types::{Campaign, CampaignProgress, Race, UnitKind, Veteran},
+};
+
+use super::{CurrentState, State};
+
+/// Whether the running campaign has yet to unlock this unit type for its race.
+pub(super) fn locked(campaign: &Option<CampaignProgress>, kind: UnitKind) -> bool {
+    matches!(campaign, Some(p) if p.race == kind.race() && !p.unlocked.contains(&kind))
+}
+
+impl State {
+    pub(super) fn open_campaigns(&mut self) {
+        self.saved = match CampaignProgress::load_all() {
+            Ok(saved) => {
+                self.campaign_status.clear();
+                saved
+            }
+            Err(e) => {
+                self.campaign_status = format!("Could not read campaign progress: {}", e);
+                Vec::new()
+            }
+        };
+        self.curr_state = CurrentState::MissionSelect;
+    }
+
+    /// Saves campaign progress, noting on the campaign screens if it fails.
+    fn save_progress(&mut self, progress: &CampaignProgress) {
+        self.campaign_status = match progress.save() {
+            Ok(()) => String::new(),
+            Err(e) => format!("Could not save campaign: {}", e),
+        };
+    }
+
+    fn progress(&self, race: Race) -> CampaignProgress {
+        self.saved
+            .iter()
+            .find(|p| p.race == race)
+            .cloned()
+            .unwrap_or_else(|| CampaignProgress::new(race))
+    }
+
+    /// Saves a won mission for the running campaign, keeping every surviving unit of its race.
+    pub(super) fn finish_mission(&mut self, winner: Race) {
+        let mut query = <(Read<Unit>,)>::query();
+
+        let mut progress = match &self.campaign {
+            Some(progress) if progress.race == winner => progress.clone(),
+            _ => return,
+        };
+        let mut roster = Vec::new();
+        for (unit,) in query.iter(&self.world) {
+            if unit.race() == winner && unit.hp() > 0 {
+                roster.push(Veteran {
+                    kind: unit.kind(),
+                    xp: unit.xp(),
+                });
+                roster.extend(unit.cargo().iter().map(|(_, u)| Veteran {
+                    kind: u.kind(),
+                    xp: u.xp(),
+                }));
+            }
+        }
+        progress.complete_mission(roster);
+        self.save_progress(&progress);
+        self.saved.retain(|p| p.race != winner);
+        self.saved.push(progress.clone());
+        self.campaign = Some(progress);
+    }
+
+    /// Lists each race's campaign and the missions of the highlighted one.
+    pub(super) fn mission_select_state(&mut self, ctx: &mut BTerm) {
+        let mid = self.window_size.1 as i32 / 2;
+        ctx.print_centered(mid - 8, "Campaign");
+
+        for (i, campaign) in Campaign::all().iter().enumerate() {
+            let progress = self.progress(campaign.race);
+            ctx.print_color_centered(
+                mid - 6 + i as i32,
+                self.settings.palette.color(campaign.race),
+                if campaign.race == self.campaign_race {
+                    RGB::from_u8(50, 50, 50)
+                } else {
+                    RGB::new()
+                },
+                format!(
+                    "[{}] {} ({:?}) {}/{}",
+                    self.settings.keys.name(Action::Choose(i + 1)),
+                    campaign.name,
+                    campaign.race,
+                    progress.mission,
+                    campaign.missions.len()
+                ),
+            );
+        }
+
+        let mut progress = self.progress(self.campaign_race);
+        for (i, mission) in progress.campaign().missions.iter().enumerate() {
+            let mark = if i < progress.mission {
+                "done"
+            } else if i == progress.mission {
+                "next"
+            } else {
+                "    "
+            };
+            ctx.print_centered(
+                mid - 1 + i as i32,
+                format!("{} {}. {}", mark, i + 1, mission.scenario),
+            );
+        }
+        ctx.print_centered(
+            mid + 5,
+            format!(
+                "Roster: {} units  Seed: {}  Unlocked: {}",
+                progress.roster.len(),
+                progress
+                    .seed
+                    .map_or("open ground".to_string(), |seed| seed.to_string()),
+                progress
+                    .unlocked
+                    .iter()
+                    .map(|kind| kind.name())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        );
+        let key = |action| self.settings.keys.name(action);
+        let back = format!(
+            "[{}] Restart  [{}] Back",
+            key(Action::RestartCampaign),
+            key(Action::Cancel)
+        );
+        ctx.print_centered(
+            mid + 7,
+            if progress.finished() {
+                format!("Campaign complete  {}", back)
+            } else {
+                format!("[{}] Play next mission  {}", key(Action::Start), back)
+            },
+        );
+        self.print_campaign_status(ctx, mid + 9);
+
+        for action in self.settings.keys.actions(ctx) {
+            match action {
+                Action::Choose(n @ 1..=3) => {
+                    self.campaign_race = [Race::Bug, Race::Human, Race::Bionic][n - 1]
+                }
+                Action::RestartCampaign => {
+                    let progress = CampaignProgress::new(self.campaign_race);
+                    self.save_progress(&progress);
+                    self.saved.retain(|p| p.race != self.campaign_race);
+                    self.saved.push(progress);
+                }
+                Action::Start if !progress.finished() => {
+                    let mission = progress.campaign().missions[progress.mission];
+                    if let Some(i) = self
+                        .scenarios
+                        .iter()
+                        .position(|s| s.name == mission.scenario)
+                    {
+                        // The first mission fixes the campaign's seed from the menu.
+                        if progress.mission == 0 && progress.seed != self.seed {
+                            progress.seed = self.seed;
+                            self.save_progress(&progress);
+                        }
+                        self.scenario = i;
+                        self.campaign = Some(progress);
+                        self.load_scenario();
+                        // Loading clears the log, so a failed save is repeated in it afterwards.
+                        if !self.campaign_status.is_empty() {
+                            self.log(self.campaign_status.clone());
+                        }
+                        self.curr_state = CurrentState::Playing;
+                    }
+                }
+                Action::Cancel => {
+                    self.campaign = None;
+                    self.curr_state = CurrentState::Menu;
+                }
+                _ => continue,
+            }
+            // Whatever the first action did may have changed the screen.
+            break;
+        }
+    }
+
+    pub(super) fn print_campaign_status(&self, ctx: &mut BTerm, y: i32) {
+        if !self.campaign_status.is_empty() {
+            ctx.print_color_centered(
+                y,
+                RGB::from_u8(255, 80, 80),
+                RGB::new(),
+                &self.campaign_status,
+            );
+        }
+    }
+}
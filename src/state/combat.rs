@@ -0,0 +1,402 @@
+use bracket_lib::prelude::*;
+
+use legion::{IntoQuery, Read, Write};
+
+use crate::{
+    components::{GameCell, Structure, Unit, KILL_XP},
+    types::{AttackShape, Layer},
+};
+
+use super::State;
+
+/// A unit or structure caught in an attack, identified by the cell it is anchored at.
+struct Hit {
+    at: Point,
+    layer: Layer,
+    structure: bool,
+    damage: i32,
+    enemy: bool,
+}
+
+pub(super) fn in_range(from: Point, to: Point, range: i32) -> bool {
+    (from.x - to.x).abs() <= range && (from.y - to.y).abs() <= range
+}
+
+impl State {
+    pub(super) fn attack_units(&mut self) {
+        let mut query = <(Read<GameCell>, Write<Unit>)>::query();
+        let mut target_query = <(Read<GameCell>, Write<Structure>)>::query();
+
+        let hits = self.planned_hits();
+        if hits.is_empty() {
+            return;
+        }
+
+        let on_hit = query
+            .iter_mut(&mut self.world)
+            .find(|(cell, _)| cell.selected())
+            .and_then(|(_, unit)| unit.kind().on_hit());
+
+        let mut xp = 0;
+        for (cell, unit) in query.iter_mut(&mut self.world) {
+            let p = Point::new(cell.x(), cell.y());
+            if let Some(hit) = hits
+                .iter()
+                .find(|h| !h.structure && h.at == p && h.layer == unit.layer())
+            {
+                if let Some(effect) = on_hit {
+                    unit.apply_effect(effect);
+                }
+                let dealt = unit.harm(hit.damage);
+                if hit.enemy {
+                    xp += dealt;
+                    if unit.hp() <= 0 {
+                        xp += KILL_XP;
+                    }
+                }
+            }
+        }
+        for (cell, structure) in target_query.iter_mut(&mut self.world) {
+            let p = Point::new(cell.x(), cell.y());
+            if let Some(hit) = hits.iter().find(|h| h.structure && h.at == p) {
+                let dealt = structure.harm(hit.damage);
+                if hit.enemy {
+                    xp += dealt;
+                    if structure.hp() <= 0 {
+                        xp += KILL_XP;
+                    }
+                }
+            }
+        }
+        for (cell, unit) in query.iter_mut(&mut self.world) {
+            if cell.selected() {
+                unit.use_attack();
+                unit.gain_xp(xp);
+            }
+        }
+
+        let mouse = Point::new(self.mouse.x, self.mouse.y);
+        if let Some(hit) = hits.iter().find(|h| !h.structure && h.at == mouse) {
+            if self.game_rules.counterattacks {
+                self.counterattack(hit.layer);
+            }
+        }
+    }
+
+    /// A defender that survives a melee attack strikes back if the attacker is within its range.
+    fn counterattack(&mut self, layer: Layer) {
+        let mut read_query = <(Read<GameCell>, Read<Unit>)>::query();
+        let mut query = <(Read<GameCell>, Write<Unit>)>::query();
+
+        let mut attacker = None;
+        let mut defender = None;
+        for (cell, unit) in read_query.iter(&self.world) {
+            if cell.selected() {
+                attacker = Some((Point::new(cell.x(), cell.y()), unit.kind(), unit.layer()));
+            } else if cell.x() == self.mouse.x && cell.y() == self.mouse.y && unit.layer() == layer
+            {
+                defender = Some((Point::new(cell.x(), cell.y()), unit.clone()));
+            }
+        }
+
+        if let (Some((a, kind, attacker_layer)), Some((d, defender))) = (attacker, defender) {
+            if defender.hp() > 0
+                && defender.can_target(attacker_layer)
+                && in_range(a, d, 1)
+                && in_range(d, a, defender.attack_range())
+                && defender.base_damage() > 0
+            {
+                let damage = kind.damage_taken(defender.base_damage(), defender.damage_type());
+                let mut xp = 0;
+                for (cell, unit) in query.iter_mut(&mut self.world) {
+                    if cell.selected() {
+                        xp += unit.harm(damage);
+                        if unit.hp() <= 0 {
+                            xp += KILL_XP;
+                        }
+                    }
+                }
+                for (cell, unit) in query.iter_mut(&mut self.world) {
+                    if cell.x() == d.x && cell.y() == d.y && unit.layer() == layer {
+                        unit.gain_xp(xp);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Marks units of the side ending its turn that never attacked as being on overwatch.
+    pub(super) fn declare_overwatch(&mut self) {
+        let mut query = <(Write<Unit>,)>::query();
+
+        for (unit,) in query.iter_mut(&mut self.world) {
+            if unit.race() == self.turn
+                && unit.num_attacks().1 > 0
+                && unit.num_attacks().0 == unit.num_attacks().1
+            {
+                unit.set_overwatch(true);
+            }
+        }
+    }
+
+    pub(super) fn clear_overwatch(&mut self) {
+        let mut query = <(Write<Unit>,)>::query();
+
+        for (unit,) in query.iter_mut(&mut self.world) {
+            if unit.race() == self.turn {
+                unit.set_overwatch(false);
+            }
+        }
+    }
+
+    /// Enemies on overwatch take a single shot at the selected unit if any tile of its path is
+    /// within their range.
+    pub(super) fn reaction_fire(&mut self, path: &[Point]) {
+        let mut read_query = <(Read<GameCell>, Read<Unit>)>::query();
+        let mut query = <(Read<GameCell>, Write<Unit>)>::query();
+
+        let mover = match read_query
+            .iter(&self.world)
+            .find(|(cell, _)| cell.selected())
+            .map(|(_, unit)| (unit.race(), unit.kind(), unit.layer()))
+        {
+            Some(mover) => mover,
+            None => return,
+        };
+
+        let mut shots = Vec::new();
+        for (cell, unit) in query.iter_mut(&mut self.world) {
+            let p = Point::new(cell.x(), cell.y());
+            if unit.on_overwatch()
+                && unit.race() != mover.0
+                && unit.can_target(mover.2)
+                && path.iter().any(|&q| in_range(p, q, unit.attack_range()))
+            {
+                unit.set_overwatch(false);
+                shots.push((
+                    p,
+                    unit.layer(),
+                    mover.1.damage_taken(unit.base_damage(), unit.damage_type()),
+                    0,
+                ));
+            }
+        }
+
+        for (cell, unit) in query.iter_mut(&mut self.world) {
+            if cell.selected() {
+                for (_, _, damage, xp) in shots.iter_mut() {
+                    if unit.hp() > 0 {
+                        *xp = unit.harm(*damage);
+                        if unit.hp() <= 0 {
+                            *xp += KILL_XP;
+                        }
+                    }
+                }
+            }
+        }
+        for (cell, unit) in query.iter_mut(&mut self.world) {
+            for (p, layer, _, xp) in &shots {
+                if cell.x() == p.x && cell.y() == p.y && unit.layer() == *layer {
+                    unit.gain_xp(*xp);
+                }
+            }
+        }
+    }
+
+    /// Everything the selected unit's attack aimed at the mouse would hit, after falloff and
+    /// damage type multipliers but before shields and armor. Empty unless it is the attacker's
+    /// turn, it has an attack left and an enemy is caught.
+    fn planned_hits(&self) -> Vec<Hit> {
+        let mut read_query = <(Read<GameCell>, Read<Unit>)>::query();
+        let mut structure_query = <(Read<GameCell>, Read<Structure>)>::query();
+
+        let (origin, attacker) = match read_query
+            .iter(&self.world)
+            .find(|(cell, _)| cell.selected())
+        {
+            Some((cell, unit)) => (Point::new(cell.x(), cell.y()), unit.clone()),
+            None => return Vec::new(),
+        };
+        let target = Point::new(self.mouse.x, self.mouse.y);
+        if attacker.race() != self.turn
+            || attacker.num_attacks().0 <= 0
+            || attacker.damage() <= 0
+            || target == origin
+            || !in_range(origin, target, attacker.attack_range())
+        {
+            return Vec::new();
+        }
+
+        let tiles = attacker.attack_shape().tiles(origin, target);
+        let damage_at = |p: Point| {
+            tiles
+                .iter()
+                .filter(|(q, _)| *q == p)
+                .map(|(_, dist)| (attacker.damage() - attacker.falloff() * dist).max(0))
+                .max()
+        };
+
+        // A single target attack hits one thing per tile, aircraft over what is beneath them.
+        let single = attacker.attack_shape() == AttackShape::Single;
+        let mut units = read_query.iter(&self.world).collect::<Vec<_>>();
+        units.sort_by_key(|(_, unit)| unit.layer() != Layer::Air);
+
+        let mut hits: Vec<Hit> = Vec::new();
+        for (cell, unit) in units {
+            let p = Point::new(cell.x(), cell.y());
+            let enemy = unit.race() != attacker.race();
+            if cell.selected()
+                || !attacker.can_target(unit.layer())
+                || !(enemy || attacker.friendly_fire())
+                || (single && hits.iter().any(|h| h.at == p))
+            {
+                continue;
+            }
+            if let Some(damage) = damage_at(p) {
+                let damage = unit.kind().damage_taken(damage, attacker.damage_type());
+                let damage = if unit.layer() == Layer::Air {
+                    damage
+                } else {
+                    (damage - self.terrain.get(p.x, p.y).cover()).max(damage.min(1))
+                };
+                if damage > 0 {
+                    hits.push(Hit {
+                        at: p,
+                        layer: unit.layer(),
+                        structure: false,
+                        damage,
+                        enemy,
+                    });
+                }
+            }
+        }
+        for (cell, structure) in structure_query.iter(&self.world) {
+            let enemy = structure.race() != attacker.race();
+            if !(enemy || attacker.friendly_fire())
+                || !attacker.can_target(Layer::Ground)
+                || (single && hits.iter().any(|h| structure.covers(cell, h.at.x, h.at.y)))
+            {
+                continue;
+            }
+            let (w, h) = structure.footprint();
+            let damage = (0..h)
+                .flat_map(|dy| (0..w).map(move |dx| Point::new(cell.x() + dx, cell.y() + dy)))
+                .filter_map(damage_at)
+                .max();
+            if let Some(damage) = damage {
+                let damage = structure
+                    .kind()
+                    .damage_taken(damage, attacker.damage_type());
+                if damage > 0 {
+                    hits.push(Hit {
+                        at: Point::new(cell.x(), cell.y()),
+                        layer: Layer::Ground,
+                        structure: true,
+                        damage,
+                        enemy,
+                    });
+                }
+            }
+        }
+
+        if hits.iter().any(|h| h.enemy) {
+            hits
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Shades the tiles the attack would cover and shows what it would do to the hovered target
+    /// before it is committed.
+    pub(super) fn print_combat_preview(&mut self, ctx: &mut BTerm) {
+        let mut read_query = <(Read<GameCell>, Read<Unit>)>::query();
+        let mut query = <(Read<GameCell>, Read<Unit>)>::query();
+        let mut structure_query = <(Read<GameCell>, Read<Structure>)>::query();
+
+        let mouse = Point::new(self.mouse.x, self.mouse.y);
+        if let Some((cell, unit)) = read_query
+            .iter(&self.world)
+            .find(|(cell, _)| cell.selected())
+        {
+            let origin = Point::new(cell.x(), cell.y());
+            if mouse != origin && in_range(origin, mouse, unit.attack_range()) {
+                for (p, _) in unit.attack_shape().tiles(origin, mouse) {
+                    if self.in_bounds(p.x, p.y) {
+                        self.shade(ctx, p.x, p.y, RGB::from_u8(90, 0, 0));
+                    }
+                }
+            }
+        }
+
+        let hits = self.planned_hits();
+
+        let mut line = None;
+        for hit in &hits {
+            if hit.structure {
+                for (cell, structure) in structure_query.iter(&self.world) {
+                    if cell.x() == hit.at.x
+                        && cell.y() == hit.at.y
+                        && structure.covers(cell, mouse.x, mouse.y)
+                    {
+                        let hp_loss = structure.preview_harm(hit.damage);
+                        let mut text = format!(
+                            "{}: {} dmg, hp {} -> {}",
+                            structure.kind().name(),
+                            hit.damage,
+                            structure.hp(),
+                            structure.hp() - hp_loss
+                        );
+                        if structure.hp() - hp_loss <= 0 {
+                            text += " (destroyed)";
+                        }
+                        line = Some(text);
+                    }
+                }
+            } else if hit.at == mouse {
+                for (cell, unit) in query.iter(&self.world) {
+                    if cell.x() == mouse.x && cell.y() == mouse.y && unit.layer() == hit.layer {
+                        let (shield_loss, hp_loss) = unit.preview_harm(hit.damage);
+                        let mut text = format!("{}: {} dmg", unit.kind().name(), hit.damage);
+                        if shield_loss > 0 {
+                            text += &format!(
+                                ", shields {} -> {}",
+                                unit.shields(),
+                                unit.shields() - shield_loss
+                            );
+                        }
+                        text += &format!(", hp {} -> {}", unit.hp(), unit.hp() - hp_loss);
+                        if unit.hp() - hp_loss <= 0 {
+                            text += " (destroyed)";
+                        }
+                        line = Some(text);
+                    }
+                }
+            }
+        }
+        if hits.len() > 1 {
+            let others = format!(
+                "{} targets hit{}",
+                hits.len(),
+                if hits.iter().any(|h| !h.enemy) {
+                    ", including allies"
+                } else {
+                    ""
+                }
+            );
+            line = Some(match line {
+                Some(text) => format!("{}  | {}", text, others),
+                None => others,
+            });
+        }
+
+        if let Some(line) = line {
+            ctx.print_color(
+                0,
+                self.layout.panel.y1,
+                RGB::from_u8(255, 255, 255),
+                RGB::new(),
+                line,
+            );
+        }
+    }
+}
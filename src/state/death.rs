@@ -0,0 +1,107 @@
+use bracket_lib::prelude::*;
+
+use legion::{maybe_changed, IntoQuery, Read};
+
+use crate::{
+    components::{GameCell, Structure, Unit, Wreckage},
+    types::DeathEffect,
+};
+
+use super::{economy, State};
+
+impl State {
+    /// Removes destroyed units and structures, running each unit's death effect first.
+    pub(super) fn clear_cells(&mut self) {
+        let mut query = <(Read<GameCell>, Read<Unit>)>::query().filter(maybe_changed::<Unit>());
+
+        let mut deleted = Vec::new();
+        let mut dead = Vec::new();
+        for chunk in query.iter_chunks_mut(&mut self.world) {
+            for (e, (cell, unit)) in chunk.into_iter_entities() {
+                if unit.hp() <= 0 {
+                    deleted.push(e);
+                    dead.push((Point::new(cell.x(), cell.y()), unit.clone()));
+                }
+            }
+        }
+        let mut query = <(Read<Structure>,)>::query().filter(maybe_changed::<Structure>());
+        let mut events = Vec::new();
+        for chunk in query.iter_chunks_mut(&mut self.world) {
+            for (e, (structure,)) in chunk.into_iter_entities() {
+                if structure.hp() <= 0 {
+                    deleted.push(e);
+                    events.push(format!(
+                        "{:?} {} destroyed",
+                        structure.race(),
+                        structure.kind().name()
+                    ));
+                }
+            }
+        }
+
+        let mut effects = Vec::new();
+        for (p, unit) in &dead {
+            let mut event = format!("{:?} {} destroyed", unit.race(), unit.kind().name());
+            if !unit.cargo().is_empty() {
+                event += &format!(" with {} aboard", unit.cargo().len());
+            }
+            match unit.kind().on_death() {
+                Some(DeathEffect::Burst(kind, n)) => {
+                    event += &format!(", bursting into {} {}s", n, kind.name());
+                    effects.extend((0..n).map(|_| (*p, Some(kind))));
+                }
+                Some(DeathEffect::Wreckage) => {
+                    event += ", leaving wreckage";
+                    effects.push((*p, None));
+                }
+                Some(DeathEffect::ReleaseInterceptors) if unit.num_interceptors().0 > 0 => {
+                    let kind = unit.kind().launches();
+                    event += &format!(", releasing {} interceptors", unit.num_interceptors().0);
+                    effects.extend((0..unit.num_interceptors().0).map(|_| (*p, kind)));
+                }
+                _ => (),
+            }
+            events.push(event);
+        }
+
+        for e in deleted {
+            self.world.remove(e);
+        }
+        for event in events {
+            self.log(event);
+        }
+        for (p, kind) in effects {
+            match kind {
+                Some(kind) if !self.occupied_on(p.x, p.y, kind.layer()) => {
+                    economy::spawn(&mut self.world, kind, p.x, p.y)
+                }
+                Some(kind) => {
+                    if let Some(pos) = self.free_tile_near(p) {
+                        economy::spawn(&mut self.world, kind, pos.x, pos.y);
+                    }
+                }
+                None => {
+                    self.world.push((
+                        GameCell::new(p.x, p.y, ',', RGB::from_u8(110, 110, 110)),
+                        Wreckage,
+                    ));
+                }
+            }
+        }
+    }
+
+    pub(super) fn print_wreckage(&mut self, ctx: &mut BTerm) {
+        let mut query = <(Read<GameCell>, Read<Wreckage>)>::query();
+
+        for (cell, _) in query.iter(&self.world) {
+            self.print_at(
+                ctx,
+                cell.x(),
+                cell.y(),
+                cell.color(),
+                RGB::new(),
+                cell.symbol(),
+            );
+        }
+    }
+}
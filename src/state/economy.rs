@@ -0,0 +1,217 @@
+use bracket_lib::prelude::*;
+
+use legion::{component, IntoQuery, Read, TryRead, World, Write};
+
+use crate::{
+    components::{GameCell, Producer, ResourceNode, Structure, Unit},
+    types::UnitKind,
+};
+
+use super::{campaign::locked, State};
+
+pub(super) fn spawn(world: &mut World, kind: UnitKind, x: i32, y: i32) {
+    spawn_veteran(world, kind, x, y, 0);
+}
+
+/// Spawns a unit that has already earned experience, such as a campaign survivor.
+pub(super) fn spawn_veteran(world: &mut World, kind: UnitKind, x: i32, y: i32, xp: i32) {
+    let (cell, mut unit) = kind.spawn(x, y);
+    unit.gain_xp(xp);
+    if kind.produces().is_empty() {
+        world.push((cell, unit));
+    } else {
+        world.push((
+            cell,
+            unit,
+            Producer::new(kind.race(), kind.produces()).with_morph(kind.morphs()),
+        ));
+    }
+}
+
+impl State {
+    pub(super) fn queue_unit(&mut self, i: usize) {
+        let mut query = <(Read<GameCell>, Write<Producer>)>::query();
+
+        let pool = self.resources.entry(self.turn).or_insert(0);
+        for (cell, producer) in query.iter_mut(&mut self.world) {
+            if cell.selected() && producer.race() == self.turn {
+                if let Some(&kind) = producer.options().get(i) {
+                    if !locked(&self.campaign, kind)
+                        && kind.cost() <= *pool
+                        && (!producer.morphs() || producer.queue().is_empty())
+                    {
+                        *pool -= kind.cost();
+                        producer.enqueue(kind);
+                    }
+                }
+            }
+        }
+    }
+
+    pub(super) fn harvest(&mut self) {
+        let mut unit_query = <(Read<GameCell>, Read<Unit>)>::query();
+        let mut query = <(Read<GameCell>, Write<ResourceNode>)>::query();
+
+        let holders = unit_query
+            .iter(&self.world)
+            .map(|(cell, unit)| (cell.x(), cell.y(), unit.race()))
+            .collect::<Vec<_>>();
+
+        let mut depleted = Vec::new();
+        for chunk in query.iter_chunks_mut(&mut self.world) {
+            for (e, (cell, node)) in chunk.into_iter_entities() {
+                if let Some(&(_, _, race)) = holders
+                    .iter()
+                    .find(|(x, y, _)| *x == cell.x() && *y == cell.y())
+                {
+                    *self.resources.entry(race).or_insert(0) += node.harvest();
+                }
+                if node.depleted() {
+                    depleted.push(e);
+                }
+            }
+        }
+        for e in depleted {
+            self.world.remove(e);
+        }
+    }
+
+    pub(super) fn complete_production(&mut self) {
+        let mut query = <(Read<GameCell>, Write<Producer>)>::query();
+
+        let mut orders = Vec::new();
+        let mut morphs = Vec::new();
+        for chunk in query.iter_chunks_mut(&mut self.world) {
+            for (e, (cell, producer)) in chunk.into_iter_entities() {
+                let queue = producer.take_queue();
+                if producer.morphs() && !queue.is_empty() {
+                    morphs.push((e, Point::new(cell.x(), cell.y()), queue[0]));
+                } else {
+                    for kind in queue {
+                        orders.push((Point::new(cell.x(), cell.y()), kind));
+                    }
+                }
+            }
+        }
+
+        for (e, p, kind) in morphs {
+            self.world.remove(e);
+            spawn(&mut self.world, kind, p.x, p.y);
+        }
+
+        for (p, kind) in orders {
+            if let Some(pos) = self.free_tile_near(p) {
+                spawn(&mut self.world, kind, pos.x, pos.y);
+            } else {
+                *self.resources.entry(kind.race()).or_insert(0) += kind.cost();
+            }
+        }
+    }
+
+    pub(super) fn print_nodes(&mut self, ctx: &mut BTerm) {
+        let mut query = <(Read<GameCell>,)>::query().filter(component::<ResourceNode>());
+
+        for (cell,) in query.iter(&self.world) {
+            self.print_at(
+                ctx,
+                cell.x(),
+                cell.y(),
+                cell.color(),
+                RGB::new(),
+                cell.symbol(),
+            );
+        }
+    }
+
+    pub(super) fn print_resources(&mut self, ctx: &mut BTerm) {
+        ctx.print_color(
+            10,
+            1,
+            RGB::from_u8(220, 180, 0),
+            RGB::new(),
+            format!("${}", self.resources.get(&self.turn).unwrap_or(&0)),
+        );
+    }
+
+    pub(super) fn print_production(&mut self, ctx: &mut BTerm) {
+        let mut query = <(
+            Read<GameCell>,
+            Read<Producer>,
+            TryRead<Unit>,
+            TryRead<Structure>,
+        )>::query();
+
+        for (cell, producer, unit, structure) in query.iter(&self.world) {
+            if cell.selected() && producer.race() == self.turn {
+                let name = match (unit, structure) {
+                    (Some(unit), _) => unit.kind().name(),
+                    (_, Some(structure)) => structure.kind().name(),
+                    _ => "",
+                };
+                let mut line = format!(
+                    "{}: {}",
+                    name,
+                    producer
+                        .options()
+                        .iter()
+                        .enumerate()
+                        .map(|(i, kind)| {
+                            if locked(&self.campaign, *kind) {
+                                format!("{}:{} locked", i + 1, kind.name())
+                            } else {
+                                format!("{}:{} ${}", i + 1, kind.name(), kind.cost())
+                            }
+                        })
+                        .collect::<Vec<_>>()
+                        .join("  ")
+                );
+                if !producer.queue().is_empty() {
+                    line += &format!(
+                        "  | Queued: {}",
+                        producer
+                            .queue()
+                            .iter()
+                            .map(|kind| kind.name())
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    );
+                }
+                ctx.print_color(
+                    0,
+                    self.layout.panel.y1,
+                    RGB::from_u8(255, 255, 255),
+                    RGB::new(),
+                    line,
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::types::Veteran;
+
+    use super::*;
+
+    #[test]
+    fn saved_veterans_come_back_with_their_rank_bonuses() {
+        let mut unit = UnitKind::Trooper.unit();
+        unit.gain_xp(6);
+        let veteran = Veteran {
+            kind: unit.kind(),
+            xp: unit.xp(),
+        };
+        let veteran: Veteran = ron::from_str(&ron::to_string(&veteran).unwrap()).unwrap();
+
+        let mut world = World::default();
+        spawn_veteran(&mut world, veteran.kind, 0, 0, veteran.xp);
+        let mut query = <Read<Unit>>::query();
+        let spawned = query.iter(&world).next().unwrap();
+
+        assert_eq!(spawned.rank(), 2);
+        assert_eq!(spawned.max_hp(), unit.max_hp());
+        assert_eq!(spawned.base_damage(), unit.base_damage());
+        assert_eq!(spawned.move_dist(), unit.move_dist());
+    }
+}
@@ -0,0 +1,480 @@
+use bracket_lib::prelude::*;
+
+use crate::{
+    input::Action,
+    types::{Objective, Race, Region, Scenario, StructureKind, Terrain, UnitKind},
+};
+
+use super::{CurrentState, State, GREEN};
+
+/// What a click on the map does in the editor.
+#[derive(Copy, Clone, Debug)]
+enum Brush {
+    Terrain(Terrain),
+    Unit(UnitKind),
+    Structure(StructureKind),
+    Node,
+    Erase,
+    /// Toggles a control point of the race's Hold objective.
+    Hold,
+    /// Moves the race's Reach objective to a 3x3 region around the tile.
+    Reach,
+}
+
+impl Brush {
+    fn describe(&self) -> String {
+        match self {
+            Brush::Terrain(terrain) => format!("{} {}", terrain.symbol(), terrain.name()),
+            Brush::Unit(kind) => format!("{} {}", kind.symbol(), kind.name()),
+            Brush::Structure(kind) => format!("{} {}", kind.symbol(), kind.name()),
+            Brush::Node => "$ Resource node".to_string(),
+            Brush::Erase => "Erase".to_string(),
+            Brush::Hold => "Control point".to_string(),
+            Brush::Reach => "Goal region".to_string(),
+        }
+    }
+}
+
+/// A scenario being edited, along with the editor's current tool.
+pub(super) struct Editor {
+    draft: Scenario,
+    brush: Brush,
+    race: Race,
+    /// The outcome of the last save, shown until the next one.
+    status: String,
+    /// The draft as it was opened or last saved.
+    saved: Scenario,
+    /// Leaving or clearing with unsaved changes waits for the same action a second time.
+    pending: Option<Action>,
+}
+
+/// The entry after `current` in `all`, wrapping around, or the first one if `current` isn't
+/// in it.
+fn cycle<T: Copy + PartialEq>(all: &[T], current: Option<T>) -> T {
+    let i = current
+        .and_then(|c| all.iter().position(|&t| t == c))
+        .map_or(0, |i| (i + 1) % all.len());
+    all[i]
+}
+
+fn footprint(kind: StructureKind) -> (i32, i32) {
+    kind.structure().footprint()
+}
+
+impl Editor {
+    /// Whether an action that throws the draft away can go ahead, asking for it to be pressed
+    /// again first if there are unsaved changes.
+    fn confirm_discard(&mut self, action: Action, key: String) -> bool {
+        if self.draft == self.saved || self.pending == Some(action) {
+            self.pending = None;
+            return true;
+        }
+        self.pending = Some(action);
+        self.status = format!("Unsaved changes, press [{}] again to discard them", key);
+        false
+    }
+
+    fn units(&self) -> Vec<UnitKind> {
+        UnitKind::all()
+            .iter()
+            .copied()
+            .filter(|kind| kind.race() == self.race)
+            .collect()
+    }
+
+    fn structures(&self) -> Vec<StructureKind> {
+        StructureKind::all()
+            .iter()
+            .copied()
+            .filter(|kind| kind.race() == self.race)
+            .collect()
+    }
+
+    /// Removes every unit, structure and node placed on the tile. Structures are matched by
+    /// their whole footprint.
+    fn clear_tile(&mut self, x: i32, y: i32) {
+        self.draft.units.retain(|&(_, ux, uy)| (ux, uy) != (x, y));
+        self.draft.structures.retain(|&(kind, sx, sy)| {
+            let (w, h) = footprint(kind);
+            !(x >= sx && x < sx + w && y >= sy && y < sy + h)
+        });
+        self.draft.nodes.retain(|&node| node != (x, y));
+    }
+
+    fn paint(&mut self, x: i32, y: i32) {
+        match self.brush {
+            Brush::Terrain(terrain) => {
+                self.draft.terrain.retain(|&(tx, ty, _)| (tx, ty) != (x, y));
+                self.draft.terrain.push((x, y, terrain));
+            }
+            Brush::Unit(kind) => {
+                self.clear_tile(x, y);
+                self.draft.units.push((kind, x, y));
+            }
+            Brush::Structure(kind) => {
+                let (w, h) = footprint(kind);
+                for dy in 0..h {
+                    for dx in 0..w {
+                        self.clear_tile(x + dx, y + dy);
+                    }
+                }
+                self.draft.structures.push((kind, x, y));
+            }
+            Brush::Node => {
+                self.clear_tile(x, y);
+                self.draft.nodes.push((x, y));
+            }
+            Brush::Erase => {
+                self.clear_tile(x, y);
+                self.draft.terrain.retain(|&(tx, ty, _)| (tx, ty) != (x, y));
+            }
+            Brush::Hold => {
+                let race = self.race;
+                let i = self.objective(|o| matches!(o, Objective::Hold(..)));
+                match i {
+                    Some(i) => {
+                        if let (_, Objective::Hold(points, _)) = &mut self.draft.objectives[i] {
+                            if let Some(p) = points.iter().position(|&p| p == (x, y)) {
+                                points.remove(p);
+                            } else {
+                                points.push((x, y));
+                            }
+                            if points.is_empty() {
+                                self.draft.objectives.remove(i);
+                            }
+                        }
+                    }
+                    None => self
+                        .draft
+                        .objectives
+                        .push((race, Objective::Hold(vec![(x, y)], 2))),
+                }
+            }
+            Brush::Reach => {
+                let region = Region {
+                    x1: x - 1,
+                    y1: y - 1,
+                    x2: x + 1,
+                    y2: y + 1,
+                };
+                self.set_objective(
+                    |o| matches!(o, Objective::Reach(_)),
+                    Some(Objective::Reach(region)),
+                );
+            }
+        }
+    }
+
+    /// Index of the current race's objective that matches.
+    fn objective(&self, matches: impl Fn(&Objective) -> bool) -> Option<usize> {
+        self.draft
+            .objectives
+            .iter()
+            .position(|(race, o)| *race == self.race && matches(o))
+    }
+
+    /// Replaces the current race's matching objective, adding or removing it as needed.
+    fn set_objective(&mut self, matches: impl Fn(&Objective) -> bool, new: Option<Objective>) {
+        match (self.objective(matches), new) {
+            (Some(i), Some(objective)) => self.draft.objectives[i].1 = objective,
+            (Some(i), None) => {
+                self.draft.objectives.remove(i);
+            }
+            (None, Some(objective)) => self.draft.objectives.push((self.race, objective)),
+            (None, None) => (),
+        }
+    }
+
+    /// Steps the race's Survive objective through off, 5, 10 and 15 rounds.
+    fn cycle_survive(&mut self) {
+        let current = self
+            .objective(|o| matches!(o, Objective::Survive(_)))
+            .map(|i| match self.draft.objectives[i].1 {
+                Objective::Survive(n) => n,
+                _ => 0,
+            });
+        let next = match current {
+            None => Some(5),
+            Some(n) if n < 15 => Some(n + 5),
+            Some(_) => None,
+        };
+        self.set_objective(
+            |o| matches!(o, Objective::Survive(_)),
+            next.map(Objective::Survive),
+        );
+    }
+
+    /// Steps the race's Destroy objective through every enemy unit kind, then off.
+    fn cycle_destroy(&mut self) {
+        let enemies = UnitKind::all()
+            .iter()
+            .copied()
+            .filter(|kind| kind.race() != self.race)
+            .collect::<Vec<_>>();
+        let current = self
+            .objective(|o| matches!(o, Objective::Destroy(_)))
+            .and_then(|i| match self.draft.objectives[i].1 {
+                Objective::Destroy(kind) => enemies.iter().position(|&k| k == kind),
+                _ => None,
+            });
+        let next = match current {
+            None => enemies.first().copied(),
+            Some(i) => enemies.get(i + 1).copied(),
+        };
+        self.set_objective(
+            |o| matches!(o, Objective::Destroy(_)),
+            next.map(Objective::Destroy),
+        );
+    }
+
+    /// Steps the rounds the race's Hold objective needs from 1 to 5.
+    fn cycle_hold_rounds(&mut self) {
+        if let Some(i) = self.objective(|o| matches!(o, Objective::Hold(..))) {
+            if let (_, Objective::Hold(_, rounds)) = &mut self.draft.objectives[i] {
+                *rounds = *rounds % 5 + 1;
+            }
+        }
+    }
+
+    fn toggle_player(&mut self) {
+        let mut players = self.draft.players();
+        if let Some(i) = players.iter().position(|&race| race == self.race) {
+            if players.len() > 1 {
+                players.remove(i);
+            }
+        } else {
+            players.push(self.race);
+            players.sort_by_key(|race| *race as usize);
+        }
+        self.draft.players = players;
+    }
+}
+
+impl State {
+    /// Opens the editor on a copy of the scenario picked in the menu. Built-in scenarios are
+    /// saved under a new name so they are never replaced.
+    pub(super) fn open_editor(&mut self) {
+        let mut draft = self.scenarios[self.scenario].clone();
+        if Scenario::builtin().iter().any(|s| s.name == draft.name) {
+            draft.name += " (custom)";
+        }
+        self.campaign = None;
+        self.creep.clear();
+        self.events.clear();
+        self.mode = super::Mode::Select;
+        self.build_board(&draft, None);
+        self.camera = Point::new(0, 0);
+        self.editor = Some(Editor {
+            saved: draft.clone(),
+            pending: None,
+            draft,
+            brush: Brush::Terrain(Terrain::Rock),
+            race: Race::Bug,
+            status: String::new(),
+        });
+        self.curr_state = CurrentState::Editor;
+    }
+
+    pub(super) fn editor_state(&mut self, ctx: &mut BTerm) {
+        self.read_click(ctx);
+        self.update_camera(ctx);
+
+        let Some(mut editor) = self.editor.take() else {
+            self.curr_state = CurrentState::Menu;
+            return;
+        };
+
+        let mut changed = false;
+        // Terrain is painted for as long as the button is held, everything else on release.
+        let painting = match editor.brush {
+            Brush::Terrain(_) => self.mouse_pressed || self.mouse_released,
+            _ => self.mouse_released,
+        };
+        if painting && self.in_bounds(self.mouse.x, self.mouse.y) {
+            editor.paint(self.mouse.x, self.mouse.y);
+            changed = true;
+        }
+        self.mouse_released = false;
+
+        if changed || ctx.key.is_some() {
+            editor.status.clear();
+        }
+        let actions = self.settings.keys.actions(ctx);
+        if changed || !actions.is_empty() {
+            editor.pending = editor.pending.filter(|pending| actions.contains(pending));
+        }
+        for action in actions {
+            match action {
+                Action::Cancel
+                    if editor.confirm_discard(action, self.settings.keys.name(action)) =>
+                {
+                    self.scenarios = Scenario::load_all();
+                    self.scenario = self.scenario.min(self.scenarios.len() - 1);
+                    self.curr_state = CurrentState::Menu;
+                    return;
+                }
+                Action::NextRace => {
+                    editor.race = cycle(&[Race::Bug, Race::Human, Race::Bionic], Some(editor.race));
+                    editor.brush = match editor.brush {
+                        Brush::Unit(_) => Brush::Unit(editor.units()[0]),
+                        Brush::Structure(_) => Brush::Structure(editor.structures()[0]),
+                        brush => brush,
+                    };
+                }
+                Action::TerrainBrush => {
+                    let current = match editor.brush {
+                        Brush::Terrain(terrain) => Some(terrain),
+                        _ => None,
+                    };
+                    editor.brush = Brush::Terrain(cycle(Terrain::all(), current));
+                }
+                Action::UnitBrush => {
+                    let current = match editor.brush {
+                        Brush::Unit(kind) => Some(kind),
+                        _ => None,
+                    };
+                    editor.brush = Brush::Unit(cycle(&editor.units(), current));
+                }
+                Action::StructureBrush => {
+                    let current = match editor.brush {
+                        Brush::Structure(kind) => Some(kind),
+                        _ => None,
+                    };
+                    editor.brush = Brush::Structure(cycle(&editor.structures(), current));
+                }
+                Action::NodeBrush => editor.brush = Brush::Node,
+                Action::EraseBrush => editor.brush = Brush::Erase,
+                Action::HoldBrush => editor.brush = Brush::Hold,
+                Action::GoalBrush => editor.brush = Brush::Reach,
+                Action::TogglePlayer => editor.toggle_player(),
+                Action::CycleSurvive => editor.cycle_survive(),
+                Action::CycleDestroy => editor.cycle_destroy(),
+                Action::CycleHoldRounds => editor.cycle_hold_rounds(),
+                Action::ClearMap
+                    if editor.confirm_discard(action, self.settings.keys.name(action)) =>
+                {
+                    editor.draft = Scenario::new(&editor.draft.name);
+                    changed = true;
+                }
+                Action::Save => {
+                    editor.status = match editor.draft.save() {
+                        Ok(()) => {
+                            editor.saved = editor.draft.clone();
+                            format!("Saved {}", editor.draft.name)
+                        }
+                        Err(e) => format!("Could not save: {}", e),
+                    };
+                    self.scenarios = Scenario::load_all();
+                    if let Some(i) = self
+                        .scenarios
+                        .iter()
+                        .position(|s| s.name == editor.draft.name)
+                    {
+                        self.scenario = i;
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        if changed {
+            self.build_board(&editor.draft, None);
+        }
+
+        self.print_grid(ctx);
+        self.print_objectives(ctx, &editor.draft);
+        self.print_nodes(ctx);
+        self.print_structures(ctx);
+        self.print_cells(ctx);
+        self.print_minimap(ctx);
+        self.print_editor(ctx, &editor);
+
+        ctx.print_color(
+            self.pointer.x,
+            self.pointer.y,
+            RGB::named(GREEN),
+            RGB::new(),
+            "<",
+        );
+
+        self.editor = Some(editor);
+    }
+
+    fn print_editor(&self, ctx: &mut BTerm, editor: &Editor) {
+        let hints = |actions: &[(Action, &str)]| {
+            actions
+                .iter()
+                .map(|&(action, name)| format!("[{}]{}", self.settings.keys.name(action), name))
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        let players = editor
+            .draft
+            .players()
+            .iter()
+            .map(|race| format!("{:?}", race))
+            .collect::<Vec<_>>()
+            .join(" ");
+        ctx.print(0, 0, format!("{}  Players: {}", editor.draft.name, players));
+        ctx.print_color(
+            0,
+            1,
+            self.settings.palette.color(editor.race),
+            RGB::new(),
+            format!(
+                "[{}] {:?}  [{}] Player  Brush: {}",
+                self.settings.keys.name(Action::NextRace),
+                editor.race,
+                self.settings.keys.name(Action::TogglePlayer),
+                editor.brush.describe()
+            ),
+        );
+        ctx.print(
+            0,
+            2,
+            hints(&[
+                (Action::TerrainBrush, "Terrain"),
+                (Action::UnitBrush, "Unit"),
+                (Action::StructureBrush, "Building"),
+                (Action::NodeBrush, "Node"),
+                (Action::EraseBrush, "Erase"),
+                (Action::HoldBrush, "Hold"),
+                (Action::GoalBrush, "Goal"),
+                (Action::Save, "Save"),
+                (Action::Cancel, ""),
+            ]),
+        );
+
+        let y = self.layout.panel.y1;
+        let objectives = editor
+            .draft
+            .objectives
+            .iter()
+            .filter(|(race, _)| *race == editor.race)
+            .map(|(_, objective)| objective.describe())
+            .collect::<Vec<_>>()
+            .join(", ");
+        let text = if !editor.status.is_empty() {
+            editor.status.clone()
+        } else if objectives.is_empty() {
+            format!(
+                "No objectives  {}",
+                hints(&[
+                    (Action::CycleSurvive, "Survive"),
+                    (Action::CycleDestroy, "Destroy"),
+                    (Action::CycleHoldRounds, "Hold rounds"),
+                    (Action::ClearMap, "Clear"),
+                ])
+            )
+        } else {
+            objectives
+        };
+        ctx.print_color(
+            0,
+            y,
+            self.settings.palette.color(editor.race),
+            RGB::new(),
+            text,
+        );
+    }
+}
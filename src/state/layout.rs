@@ -0,0 +1,67 @@
+use bracket_lib::prelude::*;
+
+/// Rows at the top of the screen for the mode box, resources, turn and End turn button.
+const TOP_BAR_HEIGHT: i32 = 3;
+/// Consoles at least this wide get a side column for the event log.
+const SIDE_PANEL_MIN_WIDTH: i32 = 100;
+const SIDE_PANEL_WIDTH: i32 = 26;
+const END_TURN_WIDTH: i32 = 10;
+/// Columns on the left of the top bar taken by the mode box and resources.
+const TOP_BAR_LEFT: i32 = 12;
+
+/// Where each part of the interface goes on a console of a given size. Every region is given in
+/// screen tiles, with `x2` and `y2` one past the last tile.
+#[derive(Clone, Debug)]
+pub(super) struct Layout {
+    pub top_bar: Rect,
+    pub end_turn: Rect,
+    /// The part of the screen showing the map.
+    pub map: Rect,
+    /// Bottom row describing the hovered unit or the current mode's options.
+    pub panel: Rect,
+    /// Recent events: two rows of the top bar on narrow consoles, a side column on wide ones.
+    pub log: Rect,
+    /// Rows between consecutive log entries, so the top bar's log skips the turn name.
+    pub log_spacing: i32,
+}
+
+impl Layout {
+    pub fn new(width: u32, height: u32) -> Self {
+        let (w, h) = (width as i32, height as i32);
+        let top_bar = Rect::with_size(0, 0, w, TOP_BAR_HEIGHT);
+        let end_turn = Rect::with_size(w - END_TURN_WIDTH, 0, END_TURN_WIDTH, TOP_BAR_HEIGHT);
+        let panel = Rect::with_size(0, h - 1, w, 1);
+        let body_height = h - TOP_BAR_HEIGHT - 1;
+
+        if w >= SIDE_PANEL_MIN_WIDTH {
+            let map_width = w - SIDE_PANEL_WIDTH;
+            Self {
+                top_bar,
+                end_turn,
+                map: Rect::with_size(0, TOP_BAR_HEIGHT, map_width, body_height),
+                panel,
+                log: Rect::with_size(
+                    map_width + 1,
+                    TOP_BAR_HEIGHT,
+                    SIDE_PANEL_WIDTH - 1,
+                    body_height,
+                ),
+                log_spacing: 1,
+            }
+        } else {
+            Self {
+                top_bar,
+                end_turn,
+                map: Rect::with_size(0, TOP_BAR_HEIGHT, w, body_height),
+                panel,
+                log: Rect::with_size(
+                    TOP_BAR_LEFT,
+                    0,
+                    w - TOP_BAR_LEFT - END_TURN_WIDTH - 2,
+                    TOP_BAR_HEIGHT,
+                ),
+                log_spacing: 2,
+            }
+        }
+    }
+}
@@ -0,0 +1,30 @@
+use bracket_lib::prelude::*;
+
+use super::State;
+
+/// How many events are kept before the oldest are dropped.
+const MAX_EVENTS: usize = 50;
+
+impl State {
+    pub(super) fn log(&mut self, event: String) {
+        self.events.push(event);
+        if self.events.len() > MAX_EVENTS {
+            self.events.remove(0);
+        }
+    }
+
+    /// Shows the two most recent events in the top bar, above and below the turn.
+    pub(super) fn print_log(&mut self, ctx: &mut BTerm) {
+        let (log, log_spacing) = (self.layout.log, self.layout.log_spacing);
+        let rows = (log.y1..log.y2).rev().step_by(log_spacing as usize);
+        for (row, event) in rows.zip(self.events.iter().rev()) {
+            ctx.print_color(
+                log.x1,
+                row,
+                RGB::from_u8(150, 150, 150),
+                RGB::new(),
+                event.chars().take(log.width() as usize).collect::<String>(),
+            );
+        }
+    }
+}
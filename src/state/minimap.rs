@@ -0,0 +1,119 @@
+use bracket_lib::prelude::*;
+
+use std::collections::HashMap;
+
+use legion::{IntoQuery, Read};
+
+use crate::{
+    components::{GameCell, Structure, Unit},
+    types::Race,
+};
+
+use super::State;
+
+/// Largest size of the minimap, in screen tiles.
+const MINIMAP_SIZE: (i32, i32) = (24, 12);
+
+impl State {
+    /// World tiles per minimap tile, along each side.
+    fn minimap_scale(&self) -> i32 {
+        let (w, h) = (self.terrain.width(), self.terrain.height());
+        ((w + MINIMAP_SIZE.0 - 1) / MINIMAP_SIZE.0)
+            .max((h + MINIMAP_SIZE.1 - 1) / MINIMAP_SIZE.1)
+            .max(1)
+    }
+
+    /// Where the minimap sits on screen, in the bottom-right corner of the map view. `None` if
+    /// it is hidden or the whole map already fits in the view.
+    pub(super) fn minimap_rect(&self) -> Option<Rect> {
+        let (map, (w, h)) = (self.layout.map, self.view_size());
+        if !self.minimap || (self.terrain.width() <= w && self.terrain.height() <= h) {
+            return None;
+        }
+        let scale = self.minimap_scale();
+        let (mw, mh) = (
+            (self.terrain.width() + scale - 1) / scale,
+            (self.terrain.height() + scale - 1) / scale,
+        );
+        Some(Rect::with_size(map.x2 - mw - 1, map.y2 - mh - 1, mw, mh))
+    }
+
+    /// The world tile in the middle of the part of the map under a point on the minimap.
+    pub(super) fn minimap_to_world(&self, p: Point) -> Option<Point> {
+        let rect = self.minimap_rect()?;
+        let scale = self.minimap_scale();
+        rect.point_in_rect(p).then(|| {
+            Point::new(
+                (p.x - rect.x1) * scale + scale / 2,
+                (p.y - rect.y1) * scale + scale / 2,
+            )
+        })
+    }
+
+    /// Draws terrain, every unit and structure colored by race, and the part of the map in
+    /// view. There is no fog of war, so nothing is hidden.
+    pub(super) fn print_minimap(&self, ctx: &mut BTerm) {
+        let Some(rect) = self.minimap_rect() else {
+            return;
+        };
+        let scale = self.minimap_scale();
+        let mut unit_query = <(Read<GameCell>, Read<Unit>)>::query();
+        let mut structure_query = <(Read<GameCell>, Read<Structure>)>::query();
+
+        let palette = self.settings.palette;
+        let mark = |race: Race, symbol| {
+            let symbol = if self.settings.race_marks {
+                race.mark()
+            } else {
+                symbol
+            };
+            (symbol, palette.color(race))
+        };
+        let mut marks = HashMap::new();
+        for (cell, structure) in structure_query.iter(&self.world) {
+            marks.insert(
+                (cell.x() / scale, cell.y() / scale),
+                mark(structure.race(), '■'),
+            );
+        }
+        for (cell, unit) in unit_query.iter(&self.world) {
+            marks.insert((cell.x() / scale, cell.y() / scale), mark(unit.race(), '•'));
+        }
+
+        let (w, h) = self.view_size();
+        let view = Rect::with_size(
+            self.camera.x / scale,
+            self.camera.y / scale,
+            (self.camera.x + w - 1) / scale - self.camera.x / scale,
+            (self.camera.y + h - 1) / scale - self.camera.y / scale,
+        );
+
+        ctx.draw_hollow_box(
+            rect.x1 - 1,
+            rect.y1 - 1,
+            rect.width() + 1,
+            rect.height() + 1,
+            RGB::from_u8(160, 160, 160),
+            RGB::new(),
+        );
+        for j in 0..rect.height() {
+            for i in 0..rect.width() {
+                let terrain = self
+                    .terrain
+                    .get(i * scale + scale / 2, j * scale + scale / 2);
+                let on_edge = (i == view.x1 || i == view.x2) && j >= view.y1 && j <= view.y2
+                    || (j == view.y1 || j == view.y2) && i >= view.x1 && i <= view.x2;
+                let bg = if on_edge {
+                    RGB::from_u8(110, 110, 110)
+                } else {
+                    RGB::from_u8(20, 20, 20)
+                };
+                let (glyph, fg) = marks
+                    .get(&(i, j))
+                    .copied()
+                    .unwrap_or((terrain.symbol(), terrain.color()));
+                ctx.set(rect.x1 + i, rect.y1 + j, fg, bg, to_cp437(glyph));
+            }
+        }
+    }
+}
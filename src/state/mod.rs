@@ -1,206 +1,437 @@
 use bracket_lib::prelude::*;
 
-use legion::{maybe_changed, IntoQuery, Read, World, Write};
+use std::collections::{HashMap, HashSet};
+
+use legion::{component, maybe_changed, Entity, IntoQuery, Read, TryRead, World, Write};
+
+use editor::Editor;
+use layout::Layout;
 
 use crate::{
-    components::{GameCell, Unit},
-    types::Race,
+    components::{GameCell, ResourceNode, Structure, Unit, Wreckage},
+    input::Action,
+    settings::{Controller, Settings},
+    types::{brighten, CampaignProgress, GameRules, Layer, Map, Race, Scenario, StructureKind},
 };
 
+mod abilities;
+mod ai;
+mod camera;
+mod campaign;
+mod combat;
+mod death;
+mod economy;
+mod editor;
+mod layout;
+mod log;
+mod minimap;
+mod movement;
+mod panel;
+mod rules;
+mod scenario;
+mod settings_menu;
+mod structures;
+mod transport;
+
 const GREEN: (u8, u8, u8) = (0, 170, 0);
+/// With a tileset the map's sprites are drawn on their own console, beneath the one holding
+/// text. Without one, everything shares the map console.
+const MAP_CONSOLE: usize = 0;
+const TEXT_CONSOLE: usize = 1;
 
 enum Mode {
     Select,
     Move,
     Attack,
     Build,
+    Ability,
+    Unload,
 }
 
 #[derive(Clone, Debug)]
 pub enum CurrentState {
     Menu,
     Playing,
+    MissionSelect,
+    Editor,
+    /// The settings menu, returning to the given screen when closed.
+    Settings(Box<CurrentState>),
+    GameOver(Race),
 }
 
 pub struct State {
     curr_state: CurrentState,
     world: World,
     window_size: (u32, u32),
+    layout: Layout,
+    /// The world tile under the mouse, or off the map while it is outside the map view.
     mouse: Point,
+    /// The mouse's screen position.
+    pointer: Point,
+    /// World tile shown at the top-left of the map view.
+    camera: Point,
+    scroll_timer: f32,
+    minimap: bool,
     mouse_pressed: bool,
     mouse_released: bool,
+    game_rules: GameRules,
+    terrain: Map,
+    /// Seed for the generated map, or `None` to play on open ground.
+    seed: Option<u64>,
+    /// Digits typed into the menu's seed field while it is being edited.
+    seed_input: Option<String>,
+    /// Every scenario that can be picked from the menu, and the picked one.
+    scenarios: Vec<Scenario>,
+    scenario: usize,
+    /// The sides taking part in the current scenario, in turn order.
+    players: Vec<Race>,
+    round: i32,
+    /// Which of the scenario's triggers have already fired.
+    fired: Vec<bool>,
+    /// Rounds in a row each Hold objective has been held.
+    held: Vec<i32>,
+    /// The campaign being played, if the scenario is one of its missions.
+    campaign: Option<CampaignProgress>,
+    /// Progress of every campaign, as shown on the mission select screen.
+    saved: Vec<CampaignProgress>,
+    /// Why campaign progress could not be read or saved, shown on the campaign screens.
+    campaign_status: String,
+    campaign_race: Race,
+    /// The scenario being edited, while the editor is open.
+    editor: Option<Editor>,
     turn: Race,
+    resources: HashMap<Race, i32>,
+    creep: HashSet<(i32, i32)>,
+    blueprint: Option<StructureKind>,
+    ability: usize,
+    events: Vec<String>,
+    settings: Settings,
+    /// Row picked in the settings menu.
+    settings_row: usize,
+    /// The picked key binding is waiting for its new key.
+    rebinding: bool,
+    /// End turn has been clicked once and waits for a second click.
+    end_turn_armed: bool,
+    /// The map is drawn with the sprites of a tileset font instead of text.
+    tiles: bool,
     selected: bool,
     mode: Mode,
 }
 
 impl State {
-    pub fn new(w: u32, h: u32) -> Self {
-        let mut world = World::default();
-
-        let units = vec![
-            (
-                GameCell::new(10, 10, '*', RGB::from_u8(170, 20, 0)),
-                Unit::new_spider(),
-            ),
-            (
-                GameCell::new(11, 10, '*', RGB::from_u8(170, 20, 0)),
-                Unit::new_spider(),
-            ),
-            (
-                GameCell::new(8, 9, 'Q', RGB::from_u8(170, 20, 0)),
-                Unit::new(Race::Bug, 2).with_move_dist(1),
-            ),
-            (
-                GameCell::new(7, 10, 'J', RGB::from_u8(170, 20, 0)),
-                Unit::new(Race::Bug, 1)
-                    .with_num_moves(2)
-                    .with_attack_range(1),
-            ),
-            (
-                GameCell::new(6, 11, 'D', RGB::from_u8(170, 20, 0)),
-                Unit::new(Race::Bug, 3).with_damage(2).with_num_attacks(2),
-            ),
-        ];
-        world.extend(units.into_iter());
-
-        let units = vec![
-            (
-                GameCell::new(14, 13, 't', RGB::from_u8(175, 175, 175)),
-                Unit::new(Race::Human, 1).with_move_dist(1),
-            ),
-            (
-                GameCell::new(16, 13, 'W', RGB::from_u8(175, 175, 175)),
-                Unit::new(Race::Human, 2)
-                    .with_move_dist(1)
-                    .with_num_attacks(3)
-                    .with_attack_range(1),
-            ),
-            (
-                GameCell::new(15, 12, 'T', RGB::from_u8(175, 175, 175)),
-                Unit::new(Race::Human, 3).with_damage(2),
-            ),
-        ];
-        world.extend(units.into_iter());
-
-        let units = vec![
-            (
-                GameCell::new(20, 20, 'V', RGB::from_u8(0, 200, 0)),
-                Unit::new(Race::Bionic, 2)
-                    .with_num_moves(2)
-                    .with_num_attacks(2)
-                    .with_attack_range(1),
-            ),
-            (
-                GameCell::new(15, 20, 'Y', RGB::from_u8(0, 200, 0)),
-                Unit::new(Race::Bionic, 3).with_num_attacks(2),
-            ),
-            (
-                GameCell::new(13, 20, 'X', RGB::from_u8(0, 200, 0)),
-                Unit::new(Race::Bionic, 2)
-                    .with_num_moves(2)
-                    .with_num_attacks(2),
-            ),
-            (
-                GameCell::new(13, 21, 'A', RGB::from_u8(0, 200, 0)),
-                Unit::new(Race::Bionic, 4).with_num_attacks(4),
-            ),
-            (
-                GameCell::new(15, 22, 'H', RGB::from_u8(0, 200, 0)),
-                Unit::new_war_carrier(),
-            ),
-        ];
-        world.extend(units.into_iter());
-
+    pub fn new(w: u32, h: u32, settings: Settings) -> Self {
         Self {
             curr_state: CurrentState::Menu,
-            world,
+            world: World::default(),
             window_size: (w, h),
+            layout: Layout::new(w, h),
             mouse: Point::new(0, 0),
+            pointer: Point::new(0, 0),
+            camera: Point::new(0, 0),
+            scroll_timer: 0.0,
+            minimap: true,
             mouse_pressed: false,
             mouse_released: false,
+            game_rules: GameRules::default(),
+            terrain: Map::empty(w as i32, h as i32),
+            seed: None,
+            seed_input: None,
+            scenarios: Scenario::load_all(),
+            scenario: 0,
+            players: Vec::new(),
+            round: 1,
+            fired: Vec::new(),
+            held: Vec::new(),
+            campaign: None,
+            saved: Vec::new(),
+            campaign_status: String::new(),
+            campaign_race: Race::Bug,
+            editor: None,
             turn: Race::Bug,
+            resources: [(Race::Bug, 5), (Race::Human, 5), (Race::Bionic, 5)]
+                .into_iter()
+                .collect(),
+            creep: HashSet::new(),
+            blueprint: None,
+            ability: 0,
+            events: Vec::new(),
+            settings,
+            settings_row: 0,
+            rebinding: false,
+            end_turn_armed: false,
+            tiles: false,
             selected: false,
             mode: Mode::Select,
         }
     }
 
+    /// Draws the map with the sprites of the loaded tileset. The context must have the sheet's
+    /// console first and a text console above it.
+    pub fn with_tiles(mut self, tiles: bool) -> Self {
+        self.tiles = tiles;
+        self
+    }
+
+    /// The glyph to draw something in: its sprite when a tileset is loaded, its symbol otherwise.
+    fn glyph(&self, symbol: char, tile: FontCharType) -> FontCharType {
+        if self.tiles {
+            tile
+        } else {
+            to_cp437(symbol)
+        }
+    }
+
     fn menu_state(&mut self, ctx: &mut BTerm) {
+        let key = |action| self.settings.keys.name(action);
+        let on_off = |on| if on { "on" } else { "off" };
+
         ctx.print_centered(self.window_size.1 as i32 / 2 - 1, "PaperCraft");
         ctx.print_centered(
             self.window_size.1 as i32 / 2 + 1,
-            "Press the spacebar to start",
+            format!(
+                "[{}] Start  [{}] Campaign  [{}] Editor",
+                key(Action::Start),
+                key(Action::OpenCampaign),
+                key(Action::OpenEditor)
+            ),
+        );
+        ctx.print_centered(
+            self.window_size.1 as i32 / 2 + 2,
+            format!("[{}] Settings", key(Action::OpenSettings)),
+        );
+        ctx.print_centered(
+            self.window_size.1 as i32 / 2 + 3,
+            format!(
+                "[{}] Scenario: {}",
+                key(Action::NextScenario),
+                self.scenarios[self.scenario].name
+            ),
+        );
+        for (i, (race, objective)) in self.scenarios[self.scenario].objectives.iter().enumerate() {
+            ctx.print_color_centered(
+                self.window_size.1 as i32 / 2 + 4 + i as i32,
+                self.settings.palette.color(*race),
+                RGB::new(),
+                objective.describe(),
+            );
+        }
+        let y = self.window_size.1 as i32 / 2
+            + 5
+            + self.scenarios[self.scenario].objectives.len() as i32;
+        ctx.print_centered(
+            y,
+            format!(
+                "[{}] Counterattacks: {}",
+                key(Action::Choose(1)),
+                on_off(self.game_rules.counterattacks)
+            ),
+        );
+        ctx.print_centered(
+            y + 1,
+            format!(
+                "[{}] Reaction fire: {}",
+                key(Action::Choose(2)),
+                on_off(self.game_rules.reaction_fire)
+            ),
+        );
+        ctx.print_centered(
+            y + 2,
+            format!(
+                "[{}] Zone of control: {}",
+                key(Action::Choose(3)),
+                on_off(self.game_rules.zone_of_control)
+            ),
+        );
+        ctx.print_centered(
+            y + 3,
+            match (&self.seed_input, self.seed) {
+                // Twenty digits can go past the largest seed, which is refused rather than dropped.
+                (Some(input), _) if !input.is_empty() && input.parse::<u64>().is_err() => {
+                    format!("Seed: {}_  Too large, the largest is {}", input, u64::MAX)
+                }
+                (Some(input), _) => format!("Seed: {}_  [{}] Done", input, key(Action::Confirm)),
+                (None, seed) => format!(
+                    "[{}] Seed: {}  [{}] Random",
+                    key(Action::EditSeed),
+                    seed.map_or("open ground".to_string(), |seed| seed.to_string()),
+                    key(Action::RandomSeed)
+                ),
+            },
         );
 
-        if let Some(VirtualKeyCode::Space) = ctx.key {
-            self.curr_state = CurrentState::Playing;
+        let actions = self.settings.keys.actions(ctx);
+
+        // The seed field takes digits and backspace as typed, whatever they are bound to.
+        if let Some(input) = &mut self.seed_input {
+            match ctx.key {
+                // Digit keys run from Key1 to Key9 and then Key0.
+                Some(key)
+                    if (key as usize) <= VirtualKeyCode::Key0 as usize && input.len() < 20 =>
+                {
+                    let digit = (key as usize - VirtualKeyCode::Key1 as usize + 1) % 10;
+                    input.push_str(&digit.to_string());
+                }
+                Some(VirtualKeyCode::Back) => {
+                    input.pop();
+                }
+                _ => (),
+            }
+            for action in actions {
+                match action {
+                    Action::Confirm if input.is_empty() => {
+                        self.seed = None;
+                        self.seed_input = None;
+                        break;
+                    }
+                    Action::Confirm => {
+                        if let Ok(seed) = input.parse() {
+                            self.seed = Some(seed);
+                            self.seed_input = None;
+                        }
+                        break;
+                    }
+                    Action::Cancel => {
+                        self.seed_input = None;
+                        break;
+                    }
+                    _ => (),
+                }
+            }
+            return;
+        }
+
+        for action in actions {
+            match action {
+                Action::EditSeed => self.seed_input = Some(String::new()),
+                Action::RandomSeed => self.seed = Some(RandomNumberGenerator::new().next_u64()),
+                Action::Start => {
+                    self.campaign = None;
+                    self.load_scenario();
+                    self.curr_state = CurrentState::Playing;
+                }
+                Action::OpenCampaign => self.open_campaigns(),
+                Action::OpenEditor => self.open_editor(),
+                Action::OpenSettings => self.open_settings(),
+                Action::NextScenario => {
+                    self.scenario = (self.scenario + 1) % self.scenarios.len();
+                }
+                Action::Choose(1) => {
+                    self.game_rules.counterattacks = !self.game_rules.counterattacks
+                }
+                Action::Choose(2) => self.game_rules.reaction_fire = !self.game_rules.reaction_fire,
+                Action::Choose(3) => {
+                    self.game_rules.zone_of_control = !self.game_rules.zone_of_control
+                }
+                _ => continue,
+            }
+            // Whatever the first action did may have changed the screen.
+            break;
         }
     }
 
-    fn play_state(&mut self, ctx: &mut BTerm) {
+    /// Tracks the left button so a click acts once, when it is released.
+    fn read_click(&mut self, ctx: &mut BTerm) {
         if ctx.left_click {
             if self.mouse_pressed {
                 self.mouse_released = true;
             }
             self.mouse_pressed = !self.mouse_pressed;
         }
+    }
+
+    fn play_state(&mut self, ctx: &mut BTerm) {
+        self.pass_idle_turn();
+        self.read_click(ctx);
+        self.update_camera(ctx);
 
         self.print_grid(ctx);
 
+        let scenario = &self.scenarios[self.scenario];
+        self.print_objectives(ctx, scenario);
+
         self.print_mode(ctx);
 
-        ctx.print_centered(1, &format!("{:?}", self.turn));
+        self.print_resources(ctx);
+
+        let top_bar = self.layout.top_bar;
+        ctx.print_centered_at(
+            top_bar.center().x,
+            top_bar.center().y,
+            format!("{:?}", self.turn),
+        );
+
+        self.print_log(ctx);
 
         ctx.print_color(
-            self.mouse.x,
-            self.mouse.y,
+            self.pointer.x,
+            self.pointer.y,
             RGB::named(GREEN),
             RGB::new(),
             "<",
         );
 
+        let end_turn = self.layout.end_turn;
         let mut end_turn_box_rgb = RGB::from_u8(170, 0, 0);
-        if self.mouse.x >= self.window_size.0 as i32 - 10
-            && self.mouse.x <= self.window_size.0 as i32
-            && self.mouse.y >= 0
-            && self.mouse.y <= 2
-        {
+        if end_turn.point_in_rect(self.pointer) {
             end_turn_box_rgb = RGB::from_u8(200, 0, 0);
             if self.mouse_released {
-                self.advance_turn();
+                self.request_end_turn();
             }
+        } else if self.mouse_released {
+            self.end_turn_armed = false;
         }
 
         ctx.draw_box(
-            self.window_size.0 as i32 - 10,
-            0,
-            9,
-            2,
+            end_turn.x1,
+            end_turn.y1,
+            end_turn.width() - 1,
+            end_turn.height() - 1,
             end_turn_box_rgb,
             end_turn_box_rgb,
         );
         ctx.print_color(
-            self.window_size.0 as i32 - 9,
-            1,
+            end_turn.x1 + 1,
+            end_turn.y1 + 1,
             RGB::from_u8(255, 255, 255),
             end_turn_box_rgb,
-            "End turn",
+            if self.end_turn_armed {
+                "Confirm?"
+            } else {
+                "End turn"
+            },
         );
 
-        self.mouse = ctx.mouse_point();
+        self.print_nodes(ctx);
+
+        self.print_wreckage(ctx);
+
+        self.print_structures(ctx);
+
+        if let Mode::Move = self.mode {
+            self.print_move_preview(ctx);
+        }
 
         self.print_cells(ctx);
 
+        self.print_minimap(ctx);
+
         if self.mouse_released {
             match self.mode {
                 Mode::Select => self.select_cells(),
-                Mode::Move => self.move_cells(),
+                Mode::Move => {
+                    if !self.embark() {
+                        self.move_cells();
+                    }
+                }
                 Mode::Attack => self.attack_units(),
                 Mode::Build => {
                     if self.selected {
                         self.make_units();
+                        self.construct();
                     }
                 }
+                Mode::Ability => self.use_ability(),
+                Mode::Unload => self.disembark(),
             }
         }
 
@@ -212,38 +443,66 @@ impl State {
     }
 
     fn key_input(&mut self, ctx: &mut BTerm) {
-        if let Some(key) = ctx.key {
-            match key {
-                VirtualKeyCode::M => {
-                    if self.selected {
-                        self.mode = Mode::Move
-                    }
+        for action in self.settings.keys.actions(ctx) {
+            match action {
+                Action::EnterMoveMode if self.selected => self.mode = Mode::Move,
+                Action::EnterAttackMode if self.selected => self.mode = Mode::Attack,
+                Action::EnterBuildMode if self.selected => self.mode = Mode::Build,
+                Action::EnterAbilityMode if self.selected => {
+                    self.mode = Mode::Ability;
+                    self.ability = 0;
                 }
-                VirtualKeyCode::A => {
-                    if self.selected {
-                        self.mode = Mode::Attack
-                    }
+                Action::EnterUnloadMode if self.selected => self.mode = Mode::Unload,
+                Action::CycleNextUnit => self.select_next_unit(),
+                Action::CenterView => self.center_on_selection(),
+                Action::EndTurn => self.request_end_turn(),
+                Action::OpenSettings => self.open_settings(),
+                Action::Cancel => {
+                    self.mode = Mode::Select;
+                    self.blueprint = None;
                 }
-                VirtualKeyCode::B => {
-                    if self.selected {
-                        self.mode = Mode::Build
+                Action::Choose(n) if matches!(self.mode, Mode::Build | Mode::Ability) => {
+                    let i = n - 1;
+                    if let Mode::Ability = self.mode {
+                        self.select_ability(i);
+                    } else if !self.select_blueprint(i) {
+                        self.queue_unit(i);
                     }
                 }
-                VirtualKeyCode::Escape => self.mode = Mode::Select,
                 _ => (),
             }
         }
     }
 
+    /// Ends the turn, or asks for it again first if the settings want confirmation.
+    fn request_end_turn(&mut self) {
+        if self.end_turn_armed || !self.settings.confirm_end_turn {
+            self.end_turn_armed = false;
+            self.advance_turn();
+        } else {
+            self.end_turn_armed = true;
+        }
+    }
+
     fn print_grid(&mut self, ctx: &mut BTerm) {
-        for x in 0..self.window_size.0 {
-            for y in 3..self.window_size.1 - 1 {
-                ctx.print_color(
-                    x as i32,
-                    y as i32,
-                    RGB::from_u8(200, 200, 200),
-                    RGB::new(),
-                    ".",
+        let (w, h) = self.view_size();
+        for y in self.camera.y..self.camera.y + h {
+            for x in self.camera.x..self.camera.x + w {
+                if !self.in_bounds(x, y) {
+                    continue;
+                }
+                let terrain = self.terrain.get(x, y);
+                self.set_at(
+                    ctx,
+                    x,
+                    y,
+                    terrain.color(),
+                    if self.creep.contains(&(x, y)) {
+                        RGB::from_u8(60, 0, 60)
+                    } else {
+                        RGB::new()
+                    },
+                    self.glyph(terrain.symbol(), terrain.tile()),
                 )
             }
         }
@@ -251,7 +510,7 @@ impl State {
 
     fn print_mode(&mut self, ctx: &mut BTerm) {
         match self.mode {
-            Mode::Select => (),
+            Mode::Select => self.print_unit_panel(ctx),
             Mode::Move => {
                 ctx.draw_box(0, 0, 5, 2, RGB::from_u8(0, 175, 0), RGB::from_u8(0, 175, 0));
                 ctx.print_color(
@@ -260,7 +519,8 @@ impl State {
                     RGB::from_u8(255, 255, 255),
                     RGB::from_u8(0, 175, 0),
                     "Move",
-                )
+                );
+                self.print_unit_panel(ctx);
             }
             Mode::Attack => {
                 ctx.draw_box(0, 0, 7, 2, RGB::from_u8(175, 0, 0), RGB::from_u8(175, 0, 0));
@@ -270,7 +530,8 @@ impl State {
                     RGB::from_u8(255, 255, 255),
                     RGB::from_u8(175, 0, 0),
                     "Attack",
-                )
+                );
+                self.print_combat_preview(ctx);
             }
             Mode::Build => {
                 ctx.draw_box(0, 0, 6, 2, RGB::from_u8(0, 0, 175), RGB::from_u8(0, 0, 175));
@@ -280,7 +541,45 @@ impl State {
                     RGB::from_u8(255, 255, 255),
                     RGB::from_u8(0, 0, 175),
                     "Build",
-                )
+                );
+                self.print_production(ctx);
+                self.print_blueprints(ctx);
+            }
+            Mode::Ability => {
+                ctx.draw_box(
+                    0,
+                    0,
+                    8,
+                    2,
+                    RGB::from_u8(150, 0, 150),
+                    RGB::from_u8(150, 0, 150),
+                );
+                ctx.print_color(
+                    1,
+                    1,
+                    RGB::from_u8(255, 255, 255),
+                    RGB::from_u8(150, 0, 150),
+                    "Ability",
+                );
+                self.print_abilities(ctx);
+            }
+            Mode::Unload => {
+                ctx.draw_box(
+                    0,
+                    0,
+                    7,
+                    2,
+                    RGB::from_u8(0, 120, 120),
+                    RGB::from_u8(0, 120, 120),
+                );
+                ctx.print_color(
+                    1,
+                    1,
+                    RGB::from_u8(255, 255, 255),
+                    RGB::from_u8(0, 120, 120),
+                    "Unload",
+                );
+                self.print_cargo(ctx);
             }
         }
     }
@@ -288,165 +587,181 @@ impl State {
     fn print_cells(&mut self, ctx: &mut BTerm) {
         let mut read_query = <(Read<GameCell>, Read<Unit>)>::query();
 
-        for (cell, unit) in read_query.iter(&self.world) {
+        // Aircraft are drawn last so they show over the ground units beneath them.
+        let mut units = read_query.iter(&self.world).collect::<Vec<_>>();
+        units.sort_by_key(|(_, unit)| unit.layer() == Layer::Air);
+
+        for (cell, unit) in &units {
+            if unit.rank() > 0 {
+                self.print_at(
+                    ctx,
+                    cell.x() + 1,
+                    cell.y(),
+                    RGB::from_u8(220, 180, 0),
+                    RGB::new(),
+                    ["'", "\"", "°"][unit.rank() - 1],
+                );
+            }
+        }
+
+        for (cell, unit) in &units {
             if cell.selected() {
                 match self.mode {
-                    Mode::Move => {
-                        for x in 1..=unit.num_moves().0 {
-                            ctx.draw_hollow_box(
-                                cell.x() - unit.move_dist() * x - 1,
-                                cell.y() - unit.move_dist() * x - 1,
-                                unit.move_dist() * 2 * x + 2,
-                                unit.move_dist() * 2 * x + 2,
-                                RGB::from_u8(0, 255, 0),
-                                RGB::new(),
-                            )
-                        }
-                    }
-                    Mode::Attack => {
-                        if unit.num_attacks().0 > 0 {
-                            ctx.draw_hollow_box(
-                                cell.x() - unit.attack_range() - 1,
-                                cell.y() - unit.attack_range() - 1,
-                                unit.attack_range() * 2 + 2,
-                                unit.attack_range() * 2 + 2,
-                                RGB::from_u8(255, 0, 0),
-                                RGB::new(),
-                            )
-                        }
-                    }
-                    Mode::Build => {
-                        if unit.num_interceptors().0 > 0 {
-                            ctx.draw_hollow_box(
-                                cell.x() - 2,
-                                cell.y() - 2,
-                                4,
-                                4,
-                                RGB::from_u8(0, 0, 255),
-                                RGB::new(),
-                            )
-                        }
-                    }
+                    Mode::Attack if unit.num_attacks().0 > 0 => self.outline(
+                        ctx,
+                        cell.x() - unit.attack_range() - 1,
+                        cell.y() - unit.attack_range() - 1,
+                        unit.attack_range() * 2 + 2,
+                        unit.attack_range() * 2 + 2,
+                        RGB::from_u8(255, 0, 0),
+                    ),
+                    Mode::Build if unit.num_interceptors().0 > 0 => self.outline(
+                        ctx,
+                        cell.x() - 2,
+                        cell.y() - 2,
+                        4,
+                        4,
+                        RGB::from_u8(0, 0, 255),
+                    ),
                     _ => (),
                 }
             }
 
-            ctx.print_color(
+            let hovered = self.mouse.x == cell.x() && self.mouse.y == cell.y();
+            let (fg, bg) = self.race_cell_colors(unit.race(), cell, hovered);
+            self.set_at(
+                ctx,
                 cell.x(),
                 cell.y(),
-                if self.mouse.x == cell.x() && self.mouse.y == cell.y() {
-                    cell.color_bright()
+                fg,
+                if unit.on_overwatch() && !cell.selected() {
+                    RGB::from_u8(80, 0, 0)
                 } else {
-                    cell.color()
+                    bg
                 },
-                cell.bg_color(),
-                &cell.symbol().to_string(),
+                self.glyph(cell.symbol(), unit.kind().tile()),
             );
         }
     }
 
-    fn select_cells(&mut self) {
-        let mut query = <(Write<GameCell>,)>::query();
-
-        let mut selected = false;
-        for (cell,) in query.iter_mut(&mut self.world) {
-            if self.mouse.x == cell.x() && self.mouse.y == cell.y() {
-                cell.select();
-                selected = true;
-            } else {
-                cell.deselect();
-                self.selected = false;
-            }
-        }
-        self.selected = selected;
+    /// The colors a unit or structure of a race is drawn in, from the chosen palette.
+    pub(super) fn race_cell_colors(
+        &self,
+        race: Race,
+        cell: &GameCell,
+        hovered: bool,
+    ) -> (RGB, RGB) {
+        let palette = self.settings.palette;
+        let fg = if hovered {
+            brighten(palette.color(race))
+        } else {
+            palette.color(race)
+        };
+        let bg = if !cell.selected() && self.settings.race_backgrounds {
+            palette.background(race)
+        } else {
+            cell.bg_color()
+        };
+        (fg, bg)
     }
 
-    fn move_cells(&mut self) {
-        let mut read_query = <(Read<GameCell>, Read<Unit>)>::query();
-        let mut nested_read_query = <(Read<GameCell>, Read<Unit>)>::query();
-        let mut query = <(Write<GameCell>, Write<Unit>)>::query();
-
-        let mut can_move = false;
-        for (cell, unit) in read_query.iter(&self.world) {
-            if unit.race() == self.turn
-                && cell.selected()
-                && unit.can_move()
-                && Rect::with_exact(
-                    cell.x() - unit.move_dist(),
-                    cell.y() - unit.move_dist(),
-                    cell.x() + unit.move_dist(),
-                    cell.y() + unit.move_dist(),
-                )
-                .point_in_rect(Point::new(self.mouse.x, self.mouse.y))
-            {
-                can_move = true;
-                for (cell, _) in nested_read_query.iter(&self.world) {
-                    if cell.x() == self.mouse.x && cell.y() == self.mouse.y {
-                        can_move = false;
-                    }
+    fn select_cells(&mut self) {
+        let mut query = <(Write<GameCell>, TryRead<Structure>)>::query()
+            .filter(!component::<ResourceNode>() & !component::<Wreckage>());
+
+        // Clicking a tile again cycles through everything stacked on it.
+        let mut hits = Vec::new();
+        for chunk in query.iter_chunks_mut(&mut self.world) {
+            for (e, (cell, structure)) in chunk.into_iter_entities() {
+                let hit = match structure {
+                    Some(structure) => structure.covers(cell, self.mouse.x, self.mouse.y),
+                    None => self.mouse.x == cell.x() && self.mouse.y == cell.y(),
+                };
+                if hit {
+                    hits.push((e, cell.selected()));
                 }
             }
         }
+        let chosen = match hits.iter().position(|&(_, selected)| selected) {
+            Some(i) => Some(hits[(i + 1) % hits.len()].0),
+            None => hits.first().map(|&(e, _)| e),
+        };
 
-        if can_move {
-            for (cell, unit) in query.iter_mut(&mut self.world) {
-                if cell.selected() {
-                    cell.move_pos(self.mouse.x, self.mouse.y);
-                    unit.use_move();
-                    if unit.num_moves().0 <= 0 {
-                        self.mode = Mode::Select;
-                    }
-                    break;
+        for chunk in query.iter_chunks_mut(&mut self.world) {
+            for (e, (cell, _)) in chunk.into_iter_entities() {
+                if Some(e) == chosen {
+                    cell.select();
+                } else {
+                    cell.deselect();
                 }
             }
         }
+        self.selected = chosen.is_some();
+        self.blueprint = None;
     }
 
-    fn attack_units(&mut self) {
-        let mut read_query = <(Read<GameCell>, Read<Unit>)>::query();
-        let mut nested_read_query = <(Read<GameCell>, Read<Unit>)>::query();
-        let mut query = <(Read<GameCell>, Write<Unit>)>::query();
-
-        let mut damage = 0;
-        for (cell, unit) in read_query.iter(&self.world) {
-            if cell.selected()
-                && Rect::with_exact(
-                    cell.x() - unit.attack_range(),
-                    cell.y() - unit.attack_range(),
-                    cell.x() + unit.attack_range(),
-                    cell.y() + unit.attack_range(),
-                )
-                .point_in_rect(Point::new(self.mouse.x, self.mouse.y))
-            {
-                for (cell2, unit2) in nested_read_query.iter(&self.world) {
-                    if unit.race() != unit2.race()
-                        && cell2.x() == self.mouse.x
-                        && cell2.y() == self.mouse.y
-                    {
-                        damage = unit.damage();
-                    }
+    /// Selects the next unit of the side whose turn it is that can still move or attack, in
+    /// reading order from the one selected now, and centers the view on it.
+    fn select_next_unit(&mut self) {
+        let mut query = <(Write<GameCell>, Read<Unit>)>::query();
+
+        let mut ready = Vec::new();
+        let mut current = None;
+        for chunk in query.iter_chunks_mut(&mut self.world) {
+            for (e, (cell, unit)) in chunk.into_iter_entities() {
+                if cell.selected() {
+                    current = Some((cell.y(), cell.x()));
+                }
+                if unit.race() == self.turn && (unit.can_move() || unit.num_attacks().0 > 0) {
+                    ready.push(((cell.y(), cell.x()), e));
                 }
             }
         }
+        ready.sort_by_key(|&(pos, _)| pos);
+        let chosen = match ready.iter().find(|&&(pos, _)| Some(pos) > current) {
+            Some(&(_, e)) => e,
+            None => match ready.first() {
+                Some(&(_, e)) => e,
+                None => return,
+            },
+        };
 
-        if damage > 0 {
-            for (cell, unit) in query.iter_mut(&mut self.world) {
-                if cell.selected() {
-                    unit.use_attack();
-                } else if cell.x() == self.mouse.x && cell.y() == self.mouse.y {
-                    unit.harm(damage);
+        self.select_only(Some(chosen));
+        self.center_on_selection();
+    }
+
+    /// Selects just this entity, or nothing, and goes back to Select mode.
+    fn select_only(&mut self, chosen: Option<Entity>) {
+        let mut query = <(Write<GameCell>,)>::query();
+        for chunk in query.iter_chunks_mut(&mut self.world) {
+            for (e, (cell,)) in chunk.into_iter_entities() {
+                if Some(e) != chosen {
+                    cell.deselect();
+                } else if !cell.selected() {
+                    cell.select();
                 }
             }
         }
+        self.selected = chosen.is_some();
+        self.blueprint = None;
+        self.mode = Mode::Select;
     }
 
     fn make_units(&mut self) {
         let mut query = <(Read<GameCell>, Write<Unit>)>::query().filter(maybe_changed::<Unit>());
 
+        let air_free = !self.occupied_on(self.mouse.x, self.mouse.y, Layer::Air);
+        let ground_free = !self.occupied(self.mouse.x, self.mouse.y);
+
         let mut units = Vec::new();
         for (cell, unit) in query.iter_mut(&mut self.world) {
-            if cell.selected() && self.turn == unit.race() {
-                if let Some(interceptor) = unit.make_interceptor(self.mouse.x, self.mouse.y) {
+            let free = match unit.kind().launches().map(|kind| kind.layer()) {
+                Some(Layer::Air) => air_free,
+                Some(_) => ground_free,
+                None => false,
+            };
+            if cell.selected() && self.turn == unit.race() && free {
+                if let Some(interceptor) = unit.launch(self.mouse.x, self.mouse.y) {
                     units.push(interceptor);
                 }
             }
@@ -454,45 +769,141 @@ impl State {
         self.world.extend(units.into_iter());
     }
 
-    fn clear_cells(&mut self) {
-        let mut query = <(Read<Unit>,)>::query().filter(maybe_changed::<Unit>());
+    fn in_bounds(&self, x: i32, y: i32) -> bool {
+        x >= 0 && x < self.terrain.width() && y >= 0 && y < self.terrain.height()
+    }
+
+    /// The unit on a tile that `accepts` takes. Aircraft come before the ground units beneath
+    /// them, since they are drawn on top.
+    pub(super) fn unit_on(
+        &self,
+        p: Point,
+        accepts: impl Fn(&Unit) -> bool,
+    ) -> Option<(Entity, Unit)> {
+        let mut query = <(Read<GameCell>, Read<Unit>)>::query();
+
+        query
+            .iter_chunks(&self.world)
+            .flat_map(|chunk| chunk.into_iter_entities())
+            .filter(|(_, (cell, unit))| cell.x() == p.x && cell.y() == p.y && accepts(unit))
+            .max_by_key(|(_, (_, unit))| unit.layer() == Layer::Air)
+            .map(|(e, (_, unit))| (e, unit.clone()))
+    }
 
-        let mut deleted = Vec::new();
-        for chunk in query.iter_chunks_mut(&mut self.world) {
-            for (e, (unit,)) in chunk.into_iter_entities() {
-                if unit.hp() <= 0 {
-                    deleted.push(e);
+    fn occupied(&self, x: i32, y: i32) -> bool {
+        self.occupied_on(x, y, Layer::Ground)
+    }
+
+    /// Whether a unit on `layer` would be blocked at this tile. Aircraft fly over structures.
+    fn occupied_on(&self, x: i32, y: i32, layer: Layer) -> bool {
+        let mut query = <(Read<GameCell>, Read<Unit>)>::query();
+        let mut structure_query = <(Read<GameCell>, Read<Structure>)>::query();
+        let mut wreckage_query = <(Read<GameCell>, Read<Wreckage>)>::query();
+
+        query
+            .iter(&self.world)
+            .any(|(cell, unit)| cell.x() == x && cell.y() == y && unit.layer().blocks(layer))
+            || (layer != Layer::Air
+                && (!self.terrain.get(x, y).passable()
+                    || structure_query
+                        .iter(&self.world)
+                        .any(|(cell, structure)| structure.covers(cell, x, y))
+                    || wreckage_query
+                        .iter(&self.world)
+                        .any(|(cell, _)| cell.x() == x && cell.y() == y)))
+    }
+
+    fn free_tile_near(&self, p: Point) -> Option<Point> {
+        for r in 1..=3 {
+            for dy in -r..=r {
+                for dx in -r..=r {
+                    let (x, y) = (p.x + dx, p.y + dy);
+                    if (dx.abs() == r || dy.abs() == r)
+                        && self.in_bounds(x, y)
+                        && !self.occupied(x, y)
+                    {
+                        return Some(Point::new(x, y));
+                    }
                 }
             }
         }
-        for e in deleted {
-            self.world.remove(e);
+        None
+    }
+
+    /// Plays or skips the turn of a race no one plays, as long as a person plays one of the
+    /// others.
+    fn pass_idle_turn(&mut self) {
+        let played = |race| self.settings.controller(race) == Controller::Human;
+        if !played(self.turn) && self.players.iter().any(|&race| played(race)) {
+            if self.settings.controller(self.turn) == Controller::Computer {
+                self.play_computer_turn();
+            }
+            self.advance_turn();
         }
     }
 
     fn advance_turn(&mut self) {
-        self.turn = match self.turn {
-            Race::Bug => Race::Human,
-            Race::Human => Race::Bionic,
-            Race::Bionic => {
+        if self.game_rules.reaction_fire {
+            self.declare_overwatch();
+        }
+        let next = self
+            .players
+            .iter()
+            .position(|&race| race == self.turn)
+            .map_or(0, |i| i + 1);
+        self.turn = match self.players.get(next) {
+            Some(&race) => race,
+            None => {
                 let mut query = <(Write<Unit>,)>::query();
 
                 for (unit,) in query.iter_mut(&mut self.world) {
                     unit.recharge();
                 }
-                Race::Bug
+                let mut query = <(Write<Structure>,)>::query();
+
+                for (structure,) in query.iter_mut(&mut self.world) {
+                    structure.recharge();
+                }
+                self.harvest();
+                self.complete_production();
+                self.round += 1;
+                self.players[0]
             }
         };
+        self.clear_overwatch();
+        self.structure_fire();
+        self.structure_heal();
+        self.race_turn_start();
+
+        let mut query = <(Write<Unit>,)>::query();
+
+        for (unit,) in query.iter_mut(&mut self.world) {
+            if unit.race() == self.turn {
+                unit.tick_effects();
+            }
+        }
+
+        self.center_on_turn();
+        self.evaluate_scenario(self.turn == self.players[0]);
     }
 }
 
 impl GameState for State {
     fn tick(&mut self, ctx: &mut BTerm) {
+        ctx.set_active_console(MAP_CONSOLE);
         ctx.cls();
+        if self.tiles {
+            ctx.set_active_console(TEXT_CONSOLE);
+            ctx.cls();
+        }
 
-        match self.curr_state {
+        match self.curr_state.clone() {
             CurrentState::Menu => self.menu_state(ctx),
             CurrentState::Playing => self.play_state(ctx),
+            CurrentState::MissionSelect => self.mission_select_state(ctx),
+            CurrentState::Editor => self.editor_state(ctx),
+            CurrentState::Settings(back) => self.settings_state(ctx, *back),
+            CurrentState::GameOver(winner) => self.game_over_state(ctx, winner),
         }
     }
 }
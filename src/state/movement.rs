@@ -0,0 +1,150 @@
+use std::collections::{HashMap, VecDeque};
+
+use bracket_lib::prelude::*;
+
+use legion::{IntoQuery, Read, Write};
+
+use crate::{
+    components::{GameCell, Unit},
+    types::Layer,
+};
+
+use super::{Mode, State};
+
+impl State {
+    /// Every tile `unit` can reach with its next move, mapped to the tile it is entered from.
+    pub(super) fn reachable(
+        &self,
+        cell: &GameCell,
+        unit: &Unit,
+    ) -> HashMap<(i32, i32), (i32, i32)> {
+        let start = (cell.x(), cell.y());
+        let move_dist = self.move_dist(cell, unit);
+
+        let mut came_from = HashMap::new();
+        let mut frontier = VecDeque::from([(start, 0)]);
+        while let Some(((x, y), dist)) = frontier.pop_front() {
+            if dist >= move_dist || ((x, y) != start && self.in_zone_of_control(x, y, unit)) {
+                continue;
+            }
+            for dy in -1..=1 {
+                for dx in -1..=1 {
+                    let next = (x + dx, y + dy);
+                    if next != start
+                        && !came_from.contains_key(&next)
+                        && self.in_bounds(next.0, next.1)
+                        && !self.occupied_on(next.0, next.1, unit.layer())
+                    {
+                        came_from.insert(next, (x, y));
+                        frontier.push_back((next, dist + 1));
+                    }
+                }
+            }
+        }
+        came_from
+    }
+
+    /// Whether entering this tile ends a unit's movement under the zone of control rule.
+    /// Aircraft fly over it, and only enemies on the mover's own layer exert it.
+    fn in_zone_of_control(&self, x: i32, y: i32, mover: &Unit) -> bool {
+        let mut query = <(Read<GameCell>, Read<Unit>)>::query();
+
+        self.game_rules.zone_of_control
+            && mover.layer() != Layer::Air
+            && query.iter(&self.world).any(|(cell, unit)| {
+                unit.race() != mover.race()
+                    && unit.layer() == mover.layer()
+                    && (cell.x() - x).abs() <= 1
+                    && (cell.y() - y).abs() <= 1
+            })
+    }
+
+    /// The path the selected unit would take to the mouse, or `None` if it can't get there.
+    fn path_to_mouse(&self) -> Option<Vec<Point>> {
+        let mut query = <(Read<GameCell>, Read<Unit>)>::query();
+
+        let (cell, unit) = query
+            .iter(&self.world)
+            .find(|(cell, unit)| cell.selected() && unit.race() == self.turn && unit.can_move())?;
+        let came_from = self.reachable(cell, unit);
+
+        let mut p = (self.mouse.x, self.mouse.y);
+        came_from.get(&p)?;
+        let mut path = vec![Point::new(p.0, p.1)];
+        while let Some(&prev) = came_from.get(&p) {
+            if prev == (cell.x(), cell.y()) {
+                break;
+            }
+            path.push(Point::new(prev.0, prev.1));
+            p = prev;
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    fn selected_unit(&self) -> Option<Unit> {
+        let mut query = <(Read<GameCell>, Read<Unit>)>::query();
+
+        query
+            .iter(&self.world)
+            .find(|(cell, _)| cell.selected())
+            .map(|(_, unit)| unit.clone())
+    }
+
+    pub(super) fn move_cells(&mut self) {
+        let mut query = <(Write<GameCell>, Write<Unit>)>::query();
+
+        let path = match self.path_to_mouse() {
+            Some(path) => path,
+            None => return,
+        };
+        let stopped = match self.selected_unit() {
+            Some(unit) => self.in_zone_of_control(self.mouse.x, self.mouse.y, &unit),
+            None => return,
+        };
+
+        for (cell, unit) in query.iter_mut(&mut self.world) {
+            if cell.selected() {
+                cell.move_pos(self.mouse.x, self.mouse.y);
+                unit.use_move();
+                if stopped {
+                    unit.end_moves();
+                }
+                if unit.num_moves().0 <= 0 {
+                    self.mode = Mode::Select;
+                }
+                break;
+            }
+        }
+        if self.game_rules.reaction_fire {
+            self.reaction_fire(&path);
+        }
+    }
+
+    /// Shades the tiles the selected unit can reach and traces the path to the mouse.
+    pub(super) fn print_move_preview(&mut self, ctx: &mut BTerm) {
+        let mut query = <(Read<GameCell>, Read<Unit>)>::query();
+
+        let reachable = match query
+            .iter(&self.world)
+            .find(|(cell, unit)| cell.selected() && unit.race() == self.turn && unit.can_move())
+        {
+            Some((cell, unit)) => self.reachable(cell, unit),
+            None => return,
+        };
+        let path = self.path_to_mouse().unwrap_or_default();
+
+        for &(x, y) in reachable.keys() {
+            self.shade(
+                ctx,
+                x,
+                y,
+                if path.contains(&Point::new(x, y)) {
+                    RGB::from_u8(0, 120, 0)
+                } else {
+                    RGB::from_u8(0, 50, 0)
+                },
+            );
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use bracket_lib::prelude::*;
+
+use legion::{IntoQuery, Read};
+
+use crate::components::{GameCell, Structure, Unit};
+
+use super::State;
+
+/// Width of the hp bar drawn after the panel text.
+const HP_BAR_WIDTH: i32 = 10;
+
+impl State {
+    /// Describes the hovered unit or structure, or the selected one if nothing is hovered, on
+    /// the bottom row. Falls back to the hovered tile's terrain.
+    pub(super) fn print_unit_panel(&mut self, ctx: &mut BTerm) {
+        let mut query = <(Read<GameCell>, Read<Unit>)>::query();
+
+        let unit = self
+            .unit_on(self.mouse, |_| true)
+            .map(|(_, unit)| unit)
+            .or_else(|| {
+                query
+                    .iter(&self.world)
+                    .find(|(cell, _)| cell.selected())
+                    .map(|(_, unit)| unit.clone())
+            });
+
+        let line = if let Some(unit) = &unit {
+            let effects = unit
+                .effects()
+                .iter()
+                .map(|e| {
+                    if e.stacks > 1 {
+                        format!(" {}x{}({})", e.kind.name(), e.stacks, e.duration)
+                    } else {
+                        format!(" {}({})", e.kind.name(), e.duration)
+                    }
+                })
+                .collect::<String>();
+            let cargo = if unit.capacity() > 0 {
+                format!("  cargo {}/{}", unit.cargo().len(), unit.capacity())
+            } else {
+                String::new()
+            };
+            Some((
+                unit.race(),
+                format!(
+                    "{} ({} {}xp)  hp {}/{}  sh {}  ar {}  dmg {} {:?}  mv {}/{}  atk {}/{}{}{}",
+                    unit.kind().name(),
+                    unit.rank_name(),
+                    unit.xp(),
+                    unit.hp(),
+                    unit.max_hp(),
+                    unit.shields(),
+                    unit.armor(),
+                    unit.base_damage(),
+                    unit.damage_type(),
+                    unit.num_moves().0,
+                    unit.num_moves().1,
+                    unit.num_attacks().0,
+                    unit.num_attacks().1,
+                    cargo,
+                    effects,
+                ),
+                unit.hp(),
+                unit.max_hp(),
+            ))
+        } else {
+            let mut structure_query = <(Read<GameCell>, Read<Structure>)>::query();
+
+            structure_query
+                .iter(&self.world)
+                .find(|(cell, structure)| structure.covers(cell, self.mouse.x, self.mouse.y))
+                .or_else(|| {
+                    structure_query
+                        .iter(&self.world)
+                        .find(|(cell, _)| cell.selected())
+                })
+                .map(|(_, structure)| {
+                    (
+                        structure.race(),
+                        format!(
+                            "{}  hp {}/{}  ar {}  dmg {} {:?}",
+                            structure.kind().name(),
+                            structure.hp(),
+                            structure.max_hp(),
+                            structure.armor(),
+                            structure.damage(),
+                            structure.damage_type(),
+                        ),
+                        structure.hp(),
+                        structure.max_hp(),
+                    )
+                })
+        };
+
+        if let Some((race, mut text, hp, max_hp)) = line {
+            if self.settings.race_marks {
+                text = format!("{} {}", race.mark(), text);
+            }
+            let y = self.layout.panel.y1;
+            let bar_x = text.chars().count() as i32 + 2;
+            ctx.print_color(0, y, self.settings.palette.color(race), RGB::new(), text);
+            if bar_x + HP_BAR_WIDTH <= self.layout.panel.x2 {
+                ctx.draw_bar_horizontal(
+                    bar_x,
+                    y,
+                    HP_BAR_WIDTH,
+                    hp.max(0),
+                    max_hp,
+                    RGB::from_u8(0, 200, 0),
+                    RGB::from_u8(80, 0, 0),
+                );
+            }
+        } else if self.in_bounds(self.mouse.x, self.mouse.y) {
+            let terrain = self.terrain.get(self.mouse.x, self.mouse.y);
+            let mut text = terrain.name().to_string();
+            if !terrain.passable() {
+                text += "  impassable";
+            } else if terrain.cover() > 0 {
+                text += &format!("  cover {}", terrain.cover());
+            }
+            ctx.print_color(0, self.layout.panel.y1, terrain.color(), RGB::new(), text);
+        }
+    }
+}
@@ -0,0 +1,54 @@
+use legion::{IntoQuery, Read, Write};
+
+use crate::{
+    components::{GameCell, Unit},
+    types::{Race, UnitKind},
+};
+
+use super::State;
+
+const SHIELD_REGEN: i32 = 1;
+/// How close a Bionic has to be to a War Carrier to be repaired by it.
+const CARRIER_REPAIR_RANGE: i32 = 2;
+const CARRIER_REPAIR: i32 = 1;
+
+impl State {
+    pub(super) fn bionic_turn_start(&mut self) {
+        self.regenerate_shields();
+        self.carrier_repair();
+    }
+
+    fn regenerate_shields(&mut self) {
+        let mut query = <(Write<Unit>,)>::query();
+
+        for (unit,) in query.iter_mut(&mut self.world) {
+            if unit.race() == Race::Bionic {
+                unit.regenerate_shields(SHIELD_REGEN);
+            }
+        }
+    }
+
+    /// War Carriers repair the hulls of other Bionics around them.
+    fn carrier_repair(&mut self) {
+        let mut read_query = <(Read<GameCell>, Read<Unit>)>::query();
+        let mut query = <(Read<GameCell>, Write<Unit>)>::query();
+
+        let carriers = read_query
+            .iter(&self.world)
+            .filter(|(_, unit)| unit.kind() == UnitKind::WarCarrier)
+            .map(|(cell, _)| (cell.x(), cell.y()))
+            .collect::<Vec<_>>();
+
+        for (cell, unit) in query.iter_mut(&mut self.world) {
+            if unit.race() == Race::Bionic
+                && carriers.iter().any(|&(x, y)| {
+                    (x, y) != (cell.x(), cell.y())
+                        && (x - cell.x()).abs() <= CARRIER_REPAIR_RANGE
+                        && (y - cell.y()).abs() <= CARRIER_REPAIR_RANGE
+                })
+            {
+                unit.heal(CARRIER_REPAIR);
+            }
+        }
+    }
+}
@@ -0,0 +1,119 @@
+use bracket_lib::prelude::*;
+
+use legion::{IntoQuery, Read, Write};
+
+use crate::{
+    components::{GameCell, Structure, Unit},
+    state::economy,
+    types::{Race, StructureKind, UnitKind},
+};
+
+use super::State;
+
+/// How far creep can spread from a Hive or Queen.
+const CREEP_RANGE: i32 = 4;
+const LARVAE_PER_QUEEN: usize = 3;
+const REGENERATION: i32 = 1;
+
+impl State {
+    pub(super) fn bug_turn_start(&mut self) {
+        self.spread_creep();
+        self.spawn_larvae();
+        self.regenerate();
+    }
+
+    /// Bugs slowly knit their wounds closed on their own.
+    fn regenerate(&mut self) {
+        let mut query = <(Write<Unit>,)>::query();
+
+        for (unit,) in query.iter_mut(&mut self.world) {
+            if unit.race() == Race::Bug {
+                unit.heal(REGENERATION);
+            }
+        }
+    }
+
+    /// Bugs standing on creep move one tile further.
+    pub(super) fn creep_bonus(&self, cell: &GameCell, unit: &Unit) -> i32 {
+        if unit.race() == Race::Bug && self.creep.contains(&(cell.x(), cell.y())) {
+            1
+        } else {
+            0
+        }
+    }
+
+    /// Creep grows one tile per turn around Hives and Queens and recedes once they are gone.
+    fn spread_creep(&mut self) {
+        let mut unit_query = <(Read<GameCell>, Read<Unit>)>::query();
+        let mut structure_query = <(Read<GameCell>, Read<Structure>)>::query();
+
+        let mut sources = unit_query
+            .iter(&self.world)
+            .filter(|(_, unit)| unit.kind() == UnitKind::Queen)
+            .map(|(cell, _)| (cell.x(), cell.y(), cell.x(), cell.y()))
+            .collect::<Vec<_>>();
+        sources.extend(
+            structure_query
+                .iter(&self.world)
+                .filter(|(_, structure)| structure.kind() == StructureKind::Hive)
+                .map(|(cell, structure)| {
+                    (
+                        cell.x(),
+                        cell.y(),
+                        cell.x() + structure.footprint().0 - 1,
+                        cell.y() + structure.footprint().1 - 1,
+                    )
+                }),
+        );
+        let near = |x: i32, y: i32, d: i32| {
+            sources
+                .iter()
+                .any(|&(x1, y1, x2, y2)| x >= x1 - d && x <= x2 + d && y >= y1 - d && y <= y2 + d)
+        };
+
+        self.creep.retain(|&(x, y)| near(x, y, CREEP_RANGE));
+
+        let mut grown = Vec::new();
+        for &(x1, y1, x2, y2) in &sources {
+            for y in y1 - 1..=y2 + 1 {
+                for x in x1 - 1..=x2 + 1 {
+                    grown.push((x, y));
+                }
+            }
+        }
+        for &(x, y) in &self.creep {
+            grown.extend([(x + 1, y), (x - 1, y), (x, y + 1), (x, y - 1)]);
+        }
+        for (x, y) in grown {
+            if self.in_bounds(x, y) && near(x, y, CREEP_RANGE) {
+                self.creep.insert((x, y));
+            }
+        }
+    }
+
+    /// Each Queen spawns a Larva per turn until she is attended by enough of them.
+    fn spawn_larvae(&mut self) {
+        let mut query = <(Read<GameCell>, Read<Unit>)>::query();
+
+        let queens = query
+            .iter(&self.world)
+            .filter(|(_, unit)| unit.kind() == UnitKind::Queen)
+            .map(|(cell, _)| Point::new(cell.x(), cell.y()))
+            .collect::<Vec<_>>();
+        let mut larvae = query
+            .iter(&self.world)
+            .filter(|(_, unit)| unit.kind() == UnitKind::Larva)
+            .count();
+
+        let cap = LARVAE_PER_QUEEN * queens.len();
+        for p in queens {
+            if larvae >= cap {
+                break;
+            }
+            if let Some(pos) = self.free_tile_near(p) {
+                economy::spawn(&mut self.world, UnitKind::Larva, pos.x, pos.y);
+                larvae += 1;
+            }
+        }
+    }
+}
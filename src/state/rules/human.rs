@@ -0,0 +1,127 @@
+use bracket_lib::prelude::*;
+
+use legion::{IntoQuery, Read, Write};
+
+use crate::{
+    components::{GameCell, Unit},
+    state::structures,
+    types::{Race, UnitKind},
+};
+
+use super::State;
+
+impl State {
+    pub(super) fn human_turn_start(&mut self) {
+        self.medics_heal();
+    }
+
+    /// Medics restore 1 hp to every wounded Human next to them.
+    fn medics_heal(&mut self) {
+        let mut read_query = <(Read<GameCell>, Read<Unit>)>::query();
+        let mut query = <(Read<GameCell>, Write<Unit>)>::query();
+
+        let medics = read_query
+            .iter(&self.world)
+            .filter(|(_, unit)| unit.kind() == UnitKind::Medic)
+            .map(|(cell, _)| (cell.x(), cell.y()))
+            .collect::<Vec<_>>();
+
+        for (cell, unit) in query.iter_mut(&mut self.world) {
+            if unit.race() == Race::Human
+                && medics.iter().any(|&(x, y)| {
+                    (x, y) != (cell.x(), cell.y())
+                        && (x - cell.x()).abs() <= 1
+                        && (y - cell.y()).abs() <= 1
+                })
+            {
+                unit.heal(1);
+            }
+        }
+    }
+
+    /// Picks the structure a selected Engineer will construct on the next click.
+    pub(in crate::state) fn select_blueprint(&mut self, i: usize) -> bool {
+        let mut query = <(Read<GameCell>, Read<Unit>)>::query();
+
+        for (cell, unit) in query.iter(&self.world) {
+            if cell.selected() && unit.race() == self.turn {
+                if let Some(&kind) = unit.kind().builds().get(i) {
+                    self.blueprint = Some(kind);
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    /// Constructs the chosen blueprint next to the selected Engineer, using up its attack.
+    pub(in crate::state) fn construct(&mut self) {
+        let kind = match self.blueprint {
+            Some(kind) => kind,
+            None => return,
+        };
+        let (w, h) = kind.structure().footprint();
+        let (x, y) = (self.mouse.x, self.mouse.y);
+
+        let mut read_query = <(Read<GameCell>, Read<Unit>)>::query();
+        let mut query = <(Read<GameCell>, Write<Unit>)>::query();
+
+        let mut can_build = false;
+        for (cell, unit) in read_query.iter(&self.world) {
+            if cell.selected()
+                && unit.race() == self.turn
+                && unit.num_attacks().0 > 0
+                && unit.kind().builds().contains(&kind)
+                && (x - cell.x()).abs() <= 1
+                && (y - cell.y()).abs() <= 1
+            {
+                can_build = kind.cost() <= *self.resources.get(&self.turn).unwrap_or(&0)
+                    && (0..h).all(|dy| {
+                        (0..w).all(|dx| {
+                            self.in_bounds(x + dx, y + dy) && !self.occupied(x + dx, y + dy)
+                        })
+                    });
+            }
+        }
+
+        if can_build {
+            for (cell, unit) in query.iter_mut(&mut self.world) {
+                if cell.selected() {
+                    unit.use_attack();
+                }
+            }
+            *self.resources.entry(self.turn).or_insert(0) -= kind.cost();
+            structures::build(&mut self.world, kind, x, y);
+        }
+    }
+
+    pub(in crate::state) fn print_blueprints(&mut self, ctx: &mut BTerm) {
+        let mut query = <(Read<GameCell>, Read<Unit>)>::query();
+
+        for (cell, unit) in query.iter(&self.world) {
+            if cell.selected() && unit.race() == self.turn && !unit.kind().builds().is_empty() {
+                let mut line = format!(
+                    "{}: {}",
+                    unit.kind().name(),
+                    unit.kind()
+                        .builds()
+                        .iter()
+                        .enumerate()
+                        .map(|(i, kind)| format!("{}:{} ${}", i + 1, kind.name(), kind.cost()))
+                        .collect::<Vec<_>>()
+                        .join("  ")
+                );
+                if let Some(kind) = self.blueprint {
+                    line += &format!("  | Building: {}", kind.name());
+                }
+                ctx.print_color(
+                    0,
+                    self.layout.panel.y1,
+                    RGB::from_u8(255, 255, 255),
+                    RGB::new(),
+                    line,
+                );
+            }
+        }
+    }
+}
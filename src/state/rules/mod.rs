@@ -0,0 +1,26 @@
+use crate::{
+    components::{GameCell, Unit},
+    types::Race,
+};
+
+use super::State;
+
+mod bionic;
+mod bug;
+mod human;
+
+impl State {
+    /// Runs the mechanics unique to the race whose turn is starting.
+    pub(super) fn race_turn_start(&mut self) {
+        match self.turn {
+            Race::Bug => self.bug_turn_start(),
+            Race::Human => self.human_turn_start(),
+            Race::Bionic => self.bionic_turn_start(),
+        }
+    }
+
+    /// How far a unit can move in a single move, including race bonuses.
+    pub(super) fn move_dist(&self, cell: &GameCell, unit: &Unit) -> i32 {
+        unit.move_dist() + self.creep_bonus(cell, unit)
+    }
+}
@@ -0,0 +1,297 @@
+use bracket_lib::prelude::*;
+
+use legion::{IntoQuery, Read, World};
+
+use crate::{
+    components::{GameCell, ResourceNode, Structure, Unit},
+    input,
+    types::{Action, Map, Objective, Race, Scenario, Trigger},
+};
+
+use super::{economy, structures, CurrentState, Mode, State};
+
+/// The middle of each side's starting units, and every tile the scenario puts something on.
+fn spawn_zones(scenario: &Scenario) -> (Vec<Point>, Vec<Point>) {
+    let zones = [Race::Bug, Race::Human, Race::Bionic]
+        .into_iter()
+        .filter_map(|race| {
+            let units = scenario
+                .units
+                .iter()
+                .filter(|(kind, _, _)| kind.race() == race)
+                .collect::<Vec<_>>();
+            let n = units.len() as i32;
+            (n > 0).then(|| {
+                Point::new(
+                    units.iter().map(|(_, x, _)| x).sum::<i32>() / n,
+                    units.iter().map(|(_, _, y)| y).sum::<i32>() / n,
+                )
+            })
+        })
+        .collect();
+
+    let mut placements = scenario
+        .units
+        .iter()
+        .map(|&(_, x, y)| Point::new(x, y))
+        .collect::<Vec<_>>();
+    for &(kind, x, y) in &scenario.structures {
+        let (w, h) = kind.structure().footprint();
+        placements.extend((0..h).flat_map(|dy| (0..w).map(move |dx| Point::new(x + dx, y + dy))));
+    }
+    placements.extend(scenario.nodes.iter().map(|&(x, y)| Point::new(x, y)));
+    for (_, objective) in &scenario.objectives {
+        if let Objective::Hold(points, _) = objective {
+            placements.extend(points.iter().map(|&(x, y)| Point::new(x, y)));
+        }
+    }
+    (zones, placements)
+}
+
+impl State {
+    /// Clears the board and sets it up for the scenario picked in the menu.
+    pub(super) fn load_scenario(&mut self) {
+        let scenario = self.scenarios[self.scenario].clone();
+        let seed = match &self.campaign {
+            Some(progress) => progress.seed,
+            None => self.seed,
+        };
+
+        self.build_board(&scenario, seed);
+
+        self.players = scenario.players();
+        self.turn = self.players[0];
+        self.round = 1;
+        self.resources = [(Race::Bug, 5), (Race::Human, 5), (Race::Bionic, 5)]
+            .into_iter()
+            .collect();
+        self.creep.clear();
+        self.blueprint = None;
+        self.ability = 0;
+        self.events.clear();
+        self.selected = false;
+        self.mode = Mode::Select;
+        self.fired = vec![false; scenario.triggers.len()];
+        self.held = vec![0; scenario.objectives.len()];
+
+        if let Some(seed) = seed {
+            self.log(format!("Map seed {}", seed));
+        }
+        for (race, objective) in &scenario.objectives {
+            self.log(format!("{:?}: {}", race, objective.describe()));
+        }
+        self.center_on_turn();
+        self.evaluate_scenario(true);
+    }
+
+    /// Lays out the scenario's terrain and everything placed on it, on a map generated from
+    /// `seed` if there is one.
+    pub(super) fn build_board(&mut self, scenario: &Scenario, seed: Option<u64>) {
+        self.world = World::default();
+        self.terrain = match seed {
+            Some(seed) => {
+                let (zones, placements) = spawn_zones(scenario);
+                Map::generate(seed, scenario.size.0, scenario.size.1, &zones, &placements)
+            }
+            None => Map::empty(scenario.size.0, scenario.size.1),
+        };
+        for &(x, y, terrain) in &scenario.terrain {
+            self.terrain.set(x, y, terrain);
+        }
+
+        // A campaign's survivors take the places of its race's usual starting units.
+        let roster = match &self.campaign {
+            Some(progress) if !progress.roster.is_empty() => Some(progress.clone()),
+            _ => None,
+        };
+        let mut slots = Vec::new();
+        for &(kind, x, y) in &scenario.units {
+            if matches!(&roster, Some(p) if p.race == kind.race()) {
+                slots.push(Point::new(x, y));
+            } else {
+                economy::spawn(&mut self.world, kind, x, y);
+            }
+        }
+        for &(kind, x, y) in &scenario.structures {
+            structures::build(&mut self.world, kind, x, y);
+        }
+        if let Some(progress) = roster {
+            for (i, veteran) in progress.roster.iter().enumerate() {
+                let pos = match slots.get(i) {
+                    Some(&p) if !self.occupied(p.x, p.y) => Some(p),
+                    _ => slots.last().and_then(|&p| self.free_tile_near(p)),
+                };
+                if let Some(pos) = pos {
+                    economy::spawn_veteran(&mut self.world, veteran.kind, pos.x, pos.y, veteran.xp);
+                }
+            }
+        }
+        for &(x, y) in &scenario.nodes {
+            self.world.push((
+                GameCell::new(x, y, '$', RGB::from_u8(220, 180, 0)),
+                ResourceNode::new(10, 1),
+            ));
+        }
+    }
+
+    /// Fires triggers, tracks control points and ends the match once a side has won.
+    pub(super) fn evaluate_scenario(&mut self, round_start: bool) {
+        let mut query = <(Read<GameCell>, Read<Unit>)>::query();
+        let mut structure_query = <(Read<Structure>,)>::query();
+
+        let scenario = self.scenarios[self.scenario].clone();
+        let units = query
+            .iter(&self.world)
+            .map(|(cell, unit)| (cell.x(), cell.y(), unit.race(), unit.kind()))
+            .collect::<Vec<_>>();
+        let holds = |race: Race, x: i32, y: i32| {
+            units
+                .iter()
+                .any(|&(ux, uy, r, _)| r == race && ux == x && uy == y)
+        };
+
+        for (i, (trigger, action)) in scenario.triggers.iter().enumerate() {
+            let fires = match *trigger {
+                Trigger::Round(n) => round_start && self.round == n,
+                Trigger::Enter(race, region) => units
+                    .iter()
+                    .any(|&(x, y, r, _)| r == race && region.contains(x, y)),
+            };
+            if fires && !self.fired[i] {
+                self.fired[i] = true;
+                self.run_action(action);
+            }
+        }
+
+        if round_start {
+            for (i, (race, objective)) in scenario.objectives.iter().enumerate() {
+                if let Objective::Hold(points, _) = objective {
+                    if points.iter().all(|&(x, y)| holds(*race, x, y)) {
+                        self.held[i] += 1;
+                    } else {
+                        self.held[i] = 0;
+                    }
+                }
+            }
+        }
+
+        let mut alive = units.iter().map(|&(_, _, r, _)| r).collect::<Vec<_>>();
+        alive.extend(
+            structure_query
+                .iter(&self.world)
+                .map(|(structure,)| structure.race()),
+        );
+        alive.sort_by_key(|&r| r as i32);
+        alive.dedup();
+
+        let met = |i: usize, race: Race, objective: &Objective| match *objective {
+            Objective::Destroy(kind) => !units.iter().any(|&(_, _, r, k)| r != race && k == kind),
+            Objective::Reach(region) => units
+                .iter()
+                .any(|&(x, y, r, _)| r == race && region.contains(x, y)),
+            Objective::Survive(n) => self.round > n && alive.contains(&race),
+            Objective::Hold(_, n) => self.held[i] >= n,
+        };
+        let winner = scenario
+            .players()
+            .into_iter()
+            .find(|&race| {
+                let mut objectives = scenario
+                    .objectives
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, (r, _))| *r == race)
+                    .peekable();
+                objectives.peek().is_some() && objectives.all(|(i, (_, o))| met(i, race, o))
+            })
+            .or(if alive.len() == 1 {
+                Some(alive[0])
+            } else {
+                None
+            });
+
+        if let Some(race) = winner {
+            self.finish_mission(race);
+            self.curr_state = CurrentState::GameOver(race);
+        }
+    }
+
+    fn run_action(&mut self, action: &Action) {
+        match action {
+            Action::Spawn(units) => {
+                for &(kind, x, y) in units {
+                    let p = Point::new(x, y);
+                    let pos = if self.occupied_on(x, y, kind.layer()) {
+                        self.free_tile_near(p)
+                    } else {
+                        Some(p)
+                    };
+                    if let Some(pos) = pos {
+                        economy::spawn(&mut self.world, kind, pos.x, pos.y);
+                    }
+                }
+            }
+            Action::Message(text) => self.log(text.clone()),
+        }
+    }
+
+    /// Shades the regions and control points named by the scenario's objectives.
+    pub(super) fn print_objectives(&self, ctx: &mut BTerm, scenario: &Scenario) {
+        for (_, objective) in &scenario.objectives {
+            match objective {
+                Objective::Reach(region) => {
+                    for y in region.y1..=region.y2 {
+                        for x in region.x1..=region.x2 {
+                            if self.in_bounds(x, y) {
+                                self.shade(ctx, x, y, RGB::from_u8(50, 50, 0));
+                            }
+                        }
+                    }
+                }
+                Objective::Hold(points, _) => {
+                    for &(x, y) in points.iter() {
+                        self.shade(ctx, x, y, RGB::from_u8(110, 110, 0));
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+
+    pub(super) fn game_over_state(&mut self, ctx: &mut BTerm, winner: Race) {
+        ctx.print_color_centered(
+            self.window_size.1 as i32 / 2 - 1,
+            self.settings.palette.color(winner),
+            RGB::new(),
+            format!("{:?} wins {}", winner, self.scenarios[self.scenario].name),
+        );
+        ctx.print_centered(
+            self.window_size.1 as i32 / 2 + 1,
+            format!(
+                "Press [{}] to return to the {}",
+                self.settings.keys.name(input::Action::Start),
+                if self.campaign.is_some() {
+                    "campaign"
+                } else {
+                    "menu"
+                }
+            ),
+        );
+        if self.campaign.is_some() {
+            self.print_campaign_status(ctx, self.window_size.1 as i32 / 2 + 3);
+        }
+
+        if self
+            .settings
+            .keys
+            .actions(ctx)
+            .contains(&input::Action::Start)
+        {
+            if self.campaign.is_some() {
+                self.curr_state = CurrentState::MissionSelect;
+            } else {
+                self.curr_state = CurrentState::Menu;
+            }
+        }
+    }
+}
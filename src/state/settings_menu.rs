@@ -0,0 +1,203 @@
+use bracket_lib::prelude::*;
+
+use crate::{
+    input::{Action, KeyBinding},
+    options::MIN_SIZE,
+    settings::Controller,
+    types::{Palette, Race},
+};
+
+use super::{CurrentState, State};
+
+/// Tile sizes the settings menu steps through.
+const TILE_SIZES: &[u32] = &[8, 12, 16, 24, 32];
+/// Settings listed before the key bindings.
+const GENERAL_ROWS: usize = 11;
+/// Animation speeds the settings menu steps through.
+const ANIMATION_SPEEDS: &[f32] = &[0.5, 1.0, 2.0, 4.0];
+const RACES: [Race; 3] = [Race::Bug, Race::Human, Race::Bionic];
+/// Actions this menu needs to be left, which can't be cleared from it.
+const REQUIRED: &[Action] = &[Action::Up, Action::Down, Action::Confirm, Action::Cancel];
+
+impl State {
+    pub(super) fn open_settings(&mut self) {
+        let back = std::mem::replace(&mut self.curr_state, CurrentState::Menu);
+        self.settings_row = 0;
+        self.rebinding = false;
+        self.curr_state = CurrentState::Settings(Box::new(back));
+    }
+
+    /// Lists every setting and key binding. Up and down pick one, left and right change a
+    /// setting, confirming a binding waits for its new key, and leaving saves them all.
+    pub(super) fn settings_state(&mut self, ctx: &mut BTerm, back: CurrentState) {
+        let settings = &self.settings;
+        let actions = settings.keys.all();
+        let switch = |on| if on { "on" } else { "off" };
+        let mut rows = vec![
+            format!("Window width: {}", settings.width),
+            format!("Window height: {}", settings.height),
+            format!("Tile size: {}", settings.tile_size),
+            format!("Confirm end turn: {}", switch(settings.confirm_end_turn)),
+            format!("Race colors: {}", settings.palette.name()),
+            format!("Race backgrounds: {}", switch(settings.race_backgrounds)),
+            format!("Race marks: {}", switch(settings.race_marks)),
+            format!("Animation speed: {}x", settings.animation_speed),
+        ];
+        rows.extend(
+            RACES
+                .iter()
+                .map(|&race| format!("{:?} played by: {:?}", race, settings.controller(race))),
+        );
+        rows.extend(
+            actions
+                .iter()
+                .map(|&action| format!("{:?}: {}", action, settings.keys.names(action))),
+        );
+
+        // Only as many rows as fit are shown, scrolled to keep the picked one in sight.
+        let visible = (self.window_size.1 as usize).saturating_sub(10).max(1);
+        let first = self.settings_row.saturating_sub(visible - 1);
+        ctx.print_centered(1, "Settings");
+        for (i, row) in rows.iter().enumerate().skip(first).take(visible) {
+            let color = if i == self.settings_row {
+                RGB::from_u8(255, 255, 0)
+            } else {
+                RGB::from_u8(255, 255, 255)
+            };
+            let text = if i == self.settings_row && self.rebinding {
+                let action = actions[i - GENERAL_ROWS];
+                if REQUIRED.contains(&action) {
+                    format!("{:?}: press a key to add, Escape to stop", action)
+                } else {
+                    format!(
+                        "{:?}: press a key to add, Delete to clear, Escape to stop",
+                        action
+                    )
+                }
+            } else {
+                row.clone()
+            };
+            ctx.print_color_centered(3 + (i - first) as i32, color, RGB::new(), text);
+        }
+        let bottom = self.window_size.1 as i32;
+        let mut x = self.window_size.0 as i32 / 2 - 10;
+        for race in RACES {
+            let name = format!("{} {:?}", race.mark(), race);
+            ctx.print_color(
+                x,
+                bottom - 7,
+                self.settings.palette.color(race),
+                RGB::new(),
+                &name,
+            );
+            x += name.chars().count() as i32 + 2;
+        }
+        ctx.print_centered(
+            bottom - 5,
+            "Window and tile size apply the next time the game starts",
+        );
+        let key = |action| self.settings.keys.name(action);
+        ctx.print_centered(
+            bottom - 3,
+            format!(
+                "[{}/{}] Pick  [{}/{}] Change  [{}] Add key  [{}] Back",
+                key(Action::Up),
+                key(Action::Down),
+                key(Action::Left),
+                key(Action::Right),
+                key(Action::Confirm),
+                key(Action::Cancel)
+            ),
+        );
+
+        if self.rebinding {
+            if let Some(binding) = KeyBinding::from_key(ctx) {
+                let action = actions[self.settings_row - GENERAL_ROWS];
+                match ctx.key {
+                    Some(VirtualKeyCode::Escape) => (),
+                    Some(VirtualKeyCode::Delete) if !REQUIRED.contains(&action) => {
+                        self.settings.keys.unbind(action)
+                    }
+                    _ => self.settings.keys.bind(action, binding),
+                }
+                self.rebinding = false;
+            }
+            return;
+        }
+
+        for action in self.settings.keys.actions(ctx) {
+            let step = match action {
+                Action::Left => -1,
+                Action::Right => 1,
+                _ => 0,
+            };
+            let settings = &mut self.settings;
+            match (action, self.settings_row) {
+                (Action::Left | Action::Right, 0) => {
+                    settings.width =
+                        (settings.width as i32 + step * 10).clamp(MIN_SIZE.0 as i32, 400) as u32
+                }
+                (Action::Left | Action::Right, 1) => {
+                    settings.height =
+                        (settings.height as i32 + step * 5).clamp(MIN_SIZE.1 as i32, 200) as u32
+                }
+                (Action::Left | Action::Right, 2) => {
+                    let i = TILE_SIZES
+                        .iter()
+                        .position(|&size| size >= settings.tile_size)
+                        .unwrap_or(TILE_SIZES.len() - 1) as i32;
+                    settings.tile_size =
+                        TILE_SIZES[(i + step).clamp(0, TILE_SIZES.len() as i32 - 1) as usize];
+                }
+                (Action::Left | Action::Right | Action::Confirm, 3) => {
+                    settings.confirm_end_turn = !settings.confirm_end_turn
+                }
+                (Action::Left | Action::Right, 4) => {
+                    let palettes = Palette::all();
+                    let i = palettes
+                        .iter()
+                        .position(|&palette| palette == settings.palette)
+                        .unwrap_or(0) as i32;
+                    settings.palette =
+                        palettes[(i + step).rem_euclid(palettes.len() as i32) as usize];
+                }
+                (Action::Left | Action::Right | Action::Confirm, 5) => {
+                    settings.race_backgrounds = !settings.race_backgrounds
+                }
+                (Action::Left | Action::Right | Action::Confirm, 6) => {
+                    settings.race_marks = !settings.race_marks
+                }
+                (Action::Left | Action::Right, 7) => {
+                    let i = ANIMATION_SPEEDS
+                        .iter()
+                        .position(|&speed| speed >= settings.animation_speed)
+                        .unwrap_or(ANIMATION_SPEEDS.len() - 1) as i32;
+                    settings.animation_speed = ANIMATION_SPEEDS
+                        [(i + step).clamp(0, ANIMATION_SPEEDS.len() as i32 - 1) as usize];
+                }
+                (Action::Left | Action::Right | Action::Confirm, row @ 8..=10) => {
+                    let race = RACES[row - 8];
+                    let controller = match settings.controller(race) {
+                        Controller::Human => Controller::Computer,
+                        Controller::Computer => Controller::Passive,
+                        Controller::Passive => Controller::Human,
+                    };
+                    settings.controllers.insert(race, controller);
+                }
+                (Action::Confirm, row) if row >= GENERAL_ROWS => self.rebinding = true,
+                (Action::Up, _) => {
+                    self.settings_row = (self.settings_row + rows.len() - 1) % rows.len()
+                }
+                (Action::Down, _) => self.settings_row = (self.settings_row + 1) % rows.len(),
+                (Action::Cancel, _) => {
+                    if let Err(e) = self.settings.save() {
+                        self.log(format!("Could not save settings: {}", e));
+                    }
+                    self.curr_state = back;
+                }
+                _ => continue,
+            }
+            break;
+        }
+    }
+}
@@ -0,0 +1,139 @@
+use bracket_lib::prelude::*;
+
+use legion::{IntoQuery, Read, World, Write};
+
+use crate::{
+    components::{GameCell, Producer, Structure, Unit},
+    types::StructureKind,
+};
+
+use super::State;
+
+pub(super) fn build(world: &mut World, kind: StructureKind, x: i32, y: i32) {
+    let (cell, structure) = kind.spawn(x, y);
+    if kind.produces().is_empty() {
+        world.push((cell, structure));
+    } else {
+        world.push((cell, structure, Producer::new(kind.race(), kind.produces())));
+    }
+}
+
+impl State {
+    /// Structures with an attack fire at the closest enemies in range when their side's turn begins.
+    pub(super) fn structure_fire(&mut self) {
+        let mut unit_query = <(Read<GameCell>, Read<Unit>)>::query();
+        let mut query = <(Read<GameCell>, Write<Structure>)>::query();
+        let mut target_query = <(Read<GameCell>, Write<Unit>)>::query();
+
+        let enemies = unit_query
+            .iter(&self.world)
+            .filter(|(_, unit)| unit.race() != self.turn)
+            .map(|(cell, unit)| {
+                (
+                    Point::new(cell.x(), cell.y()),
+                    unit.layer(),
+                    unit.hp(),
+                    unit.kind(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let mut hits = Vec::new();
+        for (cell, structure) in query.iter_mut(&mut self.world) {
+            if structure.race() != self.turn {
+                continue;
+            }
+            while structure.damage() > 0 {
+                let target = enemies
+                    .iter()
+                    .filter(|(p, layer, hp, _)| {
+                        structure.distance(cell, p.x, p.y) <= structure.attack_range()
+                            && structure.can_target(*layer)
+                            && hits
+                                .iter()
+                                .filter(|(q, l, _, _)| q == p && l == layer)
+                                .map(|(_, _, d, _)| d)
+                                .sum::<i32>()
+                                < *hp
+                    })
+                    .min_by_key(|(p, _, _, _)| structure.distance(cell, p.x, p.y));
+                match target {
+                    Some((p, layer, _, kind)) => {
+                        hits.push((
+                            *p,
+                            *layer,
+                            kind.damage_taken(structure.damage(), structure.damage_type()),
+                            structure.kind().on_hit(),
+                        ));
+                        structure.use_attack();
+                    }
+                    None => break,
+                }
+            }
+        }
+
+        for (cell, unit) in target_query.iter_mut(&mut self.world) {
+            for (p, layer, damage, on_hit) in &hits {
+                if p.x == cell.x() && p.y == cell.y() && unit.layer() == *layer {
+                    if let Some(effect) = on_hit {
+                        unit.apply_effect(*effect);
+                    }
+                    unit.harm(*damage);
+                }
+            }
+        }
+    }
+
+    /// Structures with a healing aura mend wounded friendly units around them as their side's turn
+    /// begins.
+    pub(super) fn structure_heal(&mut self) {
+        let mut structure_query = <(Read<GameCell>, Read<Structure>)>::query();
+        let mut query = <(Read<GameCell>, Write<Unit>)>::query();
+
+        let auras = structure_query
+            .iter(&self.world)
+            .filter(|(_, structure)| structure.race() == self.turn)
+            .filter_map(|(cell, structure)| {
+                structure
+                    .kind()
+                    .heal_aura()
+                    .map(|(range, amount)| (cell.clone(), structure.clone(), range, amount))
+            })
+            .collect::<Vec<_>>();
+
+        for (cell, unit) in query.iter_mut(&mut self.world) {
+            if unit.race() == self.turn {
+                for (aura_cell, structure, range, amount) in &auras {
+                    if structure.distance(aura_cell, cell.x(), cell.y()) <= *range {
+                        unit.heal(*amount);
+                    }
+                }
+            }
+        }
+    }
+
+    pub(super) fn print_structures(&mut self, ctx: &mut BTerm) {
+        let mut query = <(Read<GameCell>, Read<Structure>)>::query();
+
+        for (cell, structure) in query.iter(&self.world) {
+            let hovered = structure.covers(cell, self.mouse.x, self.mouse.y);
+            if cell.selected() && structure.attack_range() > 0 {
+                self.outline(
+                    ctx,
+                    cell.x() - structure.attack_range() - 1,
+                    cell.y() - structure.attack_range() - 1,
+                    structure.attack_range() * 2 + structure.footprint().0 + 1,
+                    structure.attack_range() * 2 + structure.footprint().1 + 1,
+                    RGB::from_u8(255, 0, 0),
+                );
+            }
+            let (fg, bg) = self.race_cell_colors(structure.race(), cell, hovered);
+            let glyph = self.glyph(cell.symbol(), structure.kind().tile());
+            for dy in 0..structure.footprint().1 {
+                for dx in 0..structure.footprint().0 {
+                    self.set_at(ctx, cell.x() + dx, cell.y() + dy, fg, bg, glyph);
+                }
+            }
+        }
+    }
+}
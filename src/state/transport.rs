@@ -0,0 +1,125 @@
+use bracket_lib::prelude::*;
+
+use legion::{IntoQuery, Read, Write};
+
+use crate::{
+    components::{GameCell, Unit},
+    state::combat::in_range,
+};
+
+use super::{Mode, State};
+
+impl State {
+    /// Loads the selected infantry unit into the friendly transport it was moved onto, as long
+    /// as the transport is next to it and has room. Returns whether it boarded.
+    pub(super) fn embark(&mut self) -> bool {
+        let mut query = <(Read<GameCell>, Read<Unit>)>::query();
+        let mut transport_query = <(Read<GameCell>, Write<Unit>)>::query();
+
+        let mouse = Point::new(self.mouse.x, self.mouse.y);
+        let mut passenger = None;
+        for chunk in query.iter_chunks(&self.world) {
+            for (e, (cell, unit)) in chunk.into_iter_entities() {
+                if cell.selected()
+                    && unit.race() == self.turn
+                    && unit.kind().infantry()
+                    && unit.can_move()
+                    && in_range(Point::new(cell.x(), cell.y()), mouse, 1)
+                {
+                    passenger = Some((e, cell.clone(), unit.clone()));
+                }
+            }
+        }
+        let (e, cell, mut unit) = match passenger {
+            Some(passenger) => passenger,
+            None => return false,
+        };
+        let transport = match self.unit_on(mouse, |t| t.race() == unit.race() && t.has_room()) {
+            Some((transport, _)) => transport,
+            None => return false,
+        };
+        unit.end_moves();
+        let mut boarded = Some((cell, unit));
+        for chunk in transport_query.iter_chunks_mut(&mut self.world) {
+            for (t, (_, carrier)) in chunk.into_iter_entities() {
+                if t == transport {
+                    if let Some((cell, unit)) = boarded.take() {
+                        carrier.board(cell, unit);
+                    }
+                }
+            }
+        }
+
+        self.world.remove(e);
+        self.selected = false;
+        self.mode = Mode::Select;
+        true
+    }
+
+    /// Drops the last unit to board the selected transport on the clicked tile next to it.
+    /// Unloaded units can't move again until the next round.
+    pub(super) fn disembark(&mut self) {
+        let mut query = <(Read<GameCell>, Write<Unit>)>::query();
+
+        let (x, y) = (self.mouse.x, self.mouse.y);
+        if !self.in_bounds(x, y) || self.occupied(x, y) {
+            return;
+        }
+        let mut unloaded = None;
+        for (cell, unit) in query.iter_mut(&mut self.world) {
+            if cell.selected()
+                && unit.race() == self.turn
+                && in_range(Point::new(cell.x(), cell.y()), Point::new(x, y), 1)
+            {
+                unloaded = unit.unload();
+            }
+        }
+        if let Some((mut cell, mut unit)) = unloaded {
+            cell.move_pos(x, y);
+            unit.end_moves();
+            self.world.push((cell, unit));
+        }
+    }
+
+    /// Shades the tiles the selected transport can unload onto and lists what it carries.
+    pub(super) fn print_cargo(&mut self, ctx: &mut BTerm) {
+        let mut query = <(Read<GameCell>, Read<Unit>)>::query();
+
+        let (origin, transport) = match query
+            .iter(&self.world)
+            .find(|(cell, unit)| cell.selected() && unit.capacity() > 0)
+        {
+            Some((cell, unit)) => (Point::new(cell.x(), cell.y()), unit.clone()),
+            None => return,
+        };
+
+        if transport.race() == self.turn && !transport.cargo().is_empty() {
+            for y in origin.y - 1..=origin.y + 1 {
+                for x in origin.x - 1..=origin.x + 1 {
+                    if self.in_bounds(x, y) && !self.occupied(x, y) {
+                        self.shade(ctx, x, y, RGB::from_u8(0, 70, 70));
+                    }
+                }
+            }
+        }
+
+        ctx.print_color(
+            0,
+            self.layout.panel.y1,
+            RGB::from_u8(255, 255, 255),
+            RGB::new(),
+            format!(
+                "{}: {}/{} carried  {}",
+                transport.kind().name(),
+                transport.cargo().len(),
+                transport.capacity(),
+                transport
+                    .cargo()
+                    .iter()
+                    .map(|(_, unit)| unit.kind().name())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        );
+    }
+}
@@ -0,0 +1,46 @@
+use crate::types::{StatusEffect, UnitKind};
+
+/// What an ability can be aimed at.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Targeting {
+    /// Fires on the caster as soon as it is chosen.
+    Caster,
+    Ally,
+    Enemy,
+    /// An empty tile.
+    Tile,
+    /// A tile of a friendly structure.
+    Structure,
+    /// Every unit within the given radius of the chosen tile.
+    Area(i32),
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum AbilityEffect {
+    Heal(i32),
+    /// Restores hp to the targeted structure.
+    Repair(i32),
+    /// Toggles the Burrowed status on the caster.
+    Burrow,
+    /// Leaps next to the target, hitting it with the caster's damage and stunning it.
+    Charge,
+    /// Strips the shields of everything in the area.
+    Emp,
+    /// Deals damage to everything in the area, allies included.
+    Blast(i32),
+    Spawn(UnitKind),
+    Status(StatusEffect),
+}
+
+/// An active ability from the unit catalog, used in Ability mode.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Ability {
+    pub name: &'static str,
+    pub effect: AbilityEffect,
+    pub targeting: Targeting,
+    pub range: i32,
+    /// Rounds to wait before the ability can be used again.
+    pub cooldown: i32,
+    /// Uses per match, or `None` for unlimited.
+    pub charges: Option<i32>,
+}
@@ -0,0 +1,111 @@
+use bracket_lib::prelude::*;
+
+/// The tiles an attack covers, relative to the attacker and the tile it aims at.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum AttackShape {
+    Single,
+    /// Every tile within the radius of the aimed tile.
+    Blast(i32),
+    /// A line of the given length from the attacker towards the aimed tile.
+    Line(i32),
+    /// A 90 degree cone of the given length from the attacker towards the aimed tile.
+    Cone(i32),
+}
+
+impl AttackShape {
+    /// Covered tiles paired with how far they are from the point of impact, for falloff.
+    pub fn tiles(&self, origin: Point, target: Point) -> Vec<(Point, i32)> {
+        let dist = |a: Point, b: Point| (a.x - b.x).abs().max((a.y - b.y).abs());
+        match *self {
+            AttackShape::Single => vec![(target, 0)],
+            AttackShape::Blast(r) => (target.y - r..=target.y + r)
+                .flat_map(|y| (target.x - r..=target.x + r).map(move |x| Point::new(x, y)))
+                .map(|p| (p, dist(p, target)))
+                .collect(),
+            AttackShape::Line(len) => {
+                let far = Point::new(
+                    origin.x + (target.x - origin.x) * len,
+                    origin.y + (target.y - origin.y) * len,
+                );
+                line2d(LineAlg::Bresenham, origin, far)
+                    .into_iter()
+                    .filter(|&p| p != origin)
+                    .take(len as usize)
+                    .enumerate()
+                    .map(|(i, p)| (p, i as i32))
+                    .collect()
+            }
+            AttackShape::Cone(len) => {
+                let (ax, ay) = ((target.x - origin.x) as f32, (target.y - origin.y) as f32);
+                let a_len = (ax * ax + ay * ay).sqrt();
+                (origin.y - len..=origin.y + len)
+                    .flat_map(|y| (origin.x - len..=origin.x + len).map(move |x| Point::new(x, y)))
+                    .filter(|&p| {
+                        let (bx, by) = ((p.x - origin.x) as f32, (p.y - origin.y) as f32);
+                        let b_len = (bx * bx + by * by).sqrt();
+                        p != origin
+                            && a_len > 0.0
+                            && (ax * bx + ay * by) / (a_len * b_len)
+                                >= std::f32::consts::FRAC_1_SQRT_2
+                    })
+                    .map(|p| (p, dist(p, origin) - 1))
+                    .collect()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sorted(mut tiles: Vec<(Point, i32)>) -> Vec<(i32, i32, i32)> {
+        tiles.sort_by_key(|(p, _)| (p.y, p.x));
+        tiles.into_iter().map(|(p, d)| (p.x, p.y, d)).collect()
+    }
+
+    #[test]
+    fn single_hits_only_the_target() {
+        let tiles = AttackShape::Single.tiles(Point::new(0, 0), Point::new(3, 2));
+        assert_eq!(tiles, vec![(Point::new(3, 2), 0)]);
+    }
+
+    #[test]
+    fn blast_covers_the_radius_around_the_target() {
+        let tiles = sorted(AttackShape::Blast(1).tiles(Point::new(0, 0), Point::new(5, 5)));
+        assert_eq!(tiles.len(), 9);
+        assert!(tiles.contains(&(5, 5, 0)));
+        assert!(tiles.contains(&(4, 4, 1)));
+        assert!(tiles.contains(&(6, 5, 1)));
+    }
+
+    #[test]
+    fn line_runs_its_full_length_past_the_target() {
+        let tiles = sorted(AttackShape::Line(3).tiles(Point::new(0, 0), Point::new(1, 0)));
+        assert_eq!(tiles, vec![(1, 0, 0), (2, 0, 1), (3, 0, 2)]);
+
+        let tiles = sorted(AttackShape::Line(2).tiles(Point::new(0, 0), Point::new(-1, -1)));
+        assert_eq!(tiles, vec![(-2, -2, 1), (-1, -1, 0)]);
+    }
+
+    #[test]
+    fn cone_spreads_towards_the_target() {
+        let tiles = sorted(AttackShape::Cone(2).tiles(Point::new(0, 0), Point::new(1, 0)));
+        assert_eq!(
+            tiles,
+            vec![
+                (2, -2, 1),
+                (1, -1, 0),
+                (2, -1, 1),
+                (1, 0, 0),
+                (2, 0, 1),
+                (1, 1, 0),
+                (2, 1, 1),
+                (2, 2, 1),
+            ]
+        );
+        assert!(AttackShape::Cone(2)
+            .tiles(Point::new(0, 0), Point::new(0, 0))
+            .is_empty());
+    }
+}
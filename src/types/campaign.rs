@@ -0,0 +1,166 @@
+use std::{fs, io};
+
+use serde::{Deserialize, Serialize};
+
+use crate::types::{Race, UnitKind};
+
+/// Where campaign progress is kept between sessions.
+const PROGRESS_FILE: &str = "campaign.ron";
+
+/// One step of a campaign: the scenario to play and what winning it unlocks.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Mission {
+    pub scenario: &'static str,
+    pub unlocks: &'static [UnitKind],
+}
+
+/// A linked sequence of missions played as one race.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Campaign {
+    pub race: Race,
+    pub name: &'static str,
+    /// Unit types that can be produced from the first mission on.
+    pub unlocked: &'static [UnitKind],
+    pub missions: &'static [Mission],
+}
+
+impl Campaign {
+    pub fn all() -> &'static [Campaign] {
+        &[
+            Campaign {
+                race: Race::Bug,
+                name: "The Swarm Awakens",
+                unlocked: &[UnitKind::Spider, UnitKind::Jumper],
+                missions: &[
+                    Mission {
+                        scenario: "Queen Hunt",
+                        unlocks: &[UnitKind::Devourer, UnitKind::Queen],
+                    },
+                    Mission {
+                        scenario: "Skirmish",
+                        unlocks: &[],
+                    },
+                ],
+            },
+            Campaign {
+                race: Race::Human,
+                name: "Last Bastion",
+                unlocked: &[UnitKind::Trooper, UnitKind::Medic, UnitKind::Engineer],
+                missions: &[
+                    Mission {
+                        scenario: "Queen Hunt",
+                        unlocks: &[UnitKind::Warden],
+                    },
+                    Mission {
+                        scenario: "Hold the Line",
+                        unlocks: &[UnitKind::Tank],
+                    },
+                ],
+            },
+            Campaign {
+                race: Race::Bionic,
+                name: "Iron Tide",
+                unlocked: &[UnitKind::Vanguard, UnitKind::Skirmisher],
+                missions: &[
+                    Mission {
+                        scenario: "Hold the Line",
+                        unlocks: &[UnitKind::Sentry, UnitKind::Assault],
+                    },
+                    Mission {
+                        scenario: "Skirmish",
+                        unlocks: &[UnitKind::WarCarrier],
+                    },
+                ],
+            },
+        ]
+    }
+
+    pub fn of(race: Race) -> &'static Campaign {
+        Campaign::all()
+            .iter()
+            .find(|c| c.race == race)
+            .expect("every race has a campaign")
+    }
+}
+
+/// A surviving unit that carries its experience into the next mission.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Veteran {
+    pub kind: UnitKind,
+    pub xp: i32,
+}
+
+/// How far a race has got through its campaign.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CampaignProgress {
+    pub race: Race,
+    /// The next mission to play.
+    pub mission: usize,
+    /// Units that survived the last mission. Empty before the first one.
+    pub roster: Vec<Veteran>,
+    pub unlocked: Vec<UnitKind>,
+    /// Map seed the missions are generated from, kept from the first mission played so each
+    /// one can be replayed on the same map. Missions are played on open ground without one.
+    #[serde(default)]
+    pub seed: Option<u64>,
+}
+
+impl CampaignProgress {
+    pub fn new(race: Race) -> Self {
+        Self {
+            race,
+            mission: 0,
+            roster: Vec::new(),
+            unlocked: Campaign::of(race).unlocked.to_vec(),
+            seed: None,
+        }
+    }
+
+    pub fn campaign(&self) -> &'static Campaign {
+        Campaign::of(self.race)
+    }
+
+    pub fn finished(&self) -> bool {
+        self.mission >= self.campaign().missions.len()
+    }
+
+    /// Records a won mission, keeping the survivors and unlocking the mission's rewards.
+    pub fn complete_mission(&mut self, roster: Vec<Veteran>) {
+        if let Some(mission) = self.campaign().missions.get(self.mission) {
+            for &kind in mission.unlocks {
+                if !self.unlocked.contains(&kind) {
+                    self.unlocked.push(kind);
+                }
+            }
+            self.mission += 1;
+            self.roster = roster;
+        }
+    }
+
+    /// Every race's saved progress. A missing file means nothing is saved yet.
+    pub fn load_all() -> io::Result<Vec<CampaignProgress>> {
+        match fs::read_to_string(PROGRESS_FILE) {
+            Ok(text) => {
+                ron::from_str(&text).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Saves this race's progress alongside that of the other races. An unreadable file is left
+    /// alone so the progress in it isn't lost.
+    pub fn save(&self) -> io::Result<()> {
+        let mut all = CampaignProgress::load_all().map_err(|_| {
+            io::Error::other(format!(
+                "{} could not be read, fix or remove it first",
+                PROGRESS_FILE
+            ))
+        })?;
+        all.retain(|p| p.race != self.race);
+        all.push(self.clone());
+        let text = ron::ser::to_string_pretty(&all, ron::ser::PrettyConfig::default())
+            .map_err(io::Error::other)?;
+        fs::write(PROGRESS_FILE, text)
+    }
+}
@@ -0,0 +1,6 @@
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum DamageType {
+    Melee,
+    Ranged,
+    Explosive,
+}
@@ -0,0 +1,12 @@
+use crate::types::UnitKind;
+
+/// What a unit leaves behind when it dies.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum DeathEffect {
+    /// Bursts into this many units of the given kind.
+    Burst(UnitKind, i32),
+    /// Leaves wreckage that blocks its tile.
+    Wreckage,
+    /// Drops every interceptor it still has docked.
+    ReleaseInterceptors,
+}
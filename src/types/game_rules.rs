@@ -0,0 +1,10 @@
+/// Optional rules toggled from the menu before a match starts.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct GameRules {
+    /// Defenders that survive a melee attack strike back if the attacker is in their range.
+    pub counterattacks: bool,
+    /// Units that skip their attacks shoot at the first enemy moving through their range.
+    pub reaction_fire: bool,
+    /// Entering a tile next to an enemy ends a unit's movement for the turn.
+    pub zone_of_control: bool,
+}
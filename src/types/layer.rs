@@ -0,0 +1,23 @@
+/// The movement layer a unit occupies. Units only block others on the same layer.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Layer {
+    Ground,
+    /// Flies over ground units and structures.
+    Air,
+    /// Dug in beneath the ground; still blocks ground movement.
+    Burrowed,
+}
+
+impl Layer {
+    /// Layers that share tiles with each other for the purpose of occupancy.
+    pub fn blocks(&self, other: Layer) -> bool {
+        (*self == Layer::Air) == (other == Layer::Air)
+    }
+}
+
+/// Weapons that can only hit units on the ground.
+pub const GROUND: &[Layer] = &[Layer::Ground];
+/// Weapons that can hit ground units and aircraft.
+pub const GROUND_AIR: &[Layer] = &[Layer::Ground, Layer::Air];
+/// Weapons that can hit anything, including burrowed units.
+pub const ALL_LAYERS: &[Layer] = &[Layer::Ground, Layer::Air, Layer::Burrowed];
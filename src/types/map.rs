@@ -0,0 +1,210 @@
+use bracket_lib::prelude::*;
+
+use crate::types::Terrain;
+
+/// Chance out of 100 that a tile starts out as rock or forest before smoothing.
+const ROCK_CHANCE: i32 = 42;
+const FOREST_CHANCE: i32 = 38;
+/// Tiles kept clear around each side's spawn zone, and around everything placed on the map.
+const ZONE_RADIUS: i32 = 4;
+const PLACEMENT_RADIUS: i32 = 1;
+
+/// The terrain of every tile in the world.
+#[derive(Clone, Debug)]
+pub struct Map {
+    width: i32,
+    height: i32,
+    tiles: Vec<Terrain>,
+}
+
+impl Map {
+    pub fn empty(width: i32, height: i32) -> Self {
+        Self {
+            width,
+            height,
+            tiles: vec![Terrain::Open; (width * height) as usize],
+        }
+    }
+
+    /// Generates caves, forests, a river with fords and ruins from `seed`. The same seed and
+    /// layout always give the same map. Each spawn zone is cleared to the same radius and joined
+    /// to the next by a passable corridor so no side starts walled in.
+    pub fn generate(
+        seed: u64,
+        width: i32,
+        height: i32,
+        zones: &[Point],
+        placements: &[Point],
+    ) -> Self {
+        let mut rng = RandomNumberGenerator::seeded(seed);
+        let mut map = Map::empty(width, height);
+
+        let rock = map.automaton(&mut rng, ROCK_CHANCE, 4);
+        let forest = map.automaton(&mut rng, FOREST_CHANCE, 3);
+        for y in 0..height {
+            for x in 0..width {
+                let i = map.index(x, y);
+                if rock[i] {
+                    map.tiles[i] = Terrain::Rock;
+                } else if forest[i] {
+                    map.tiles[i] = Terrain::Forest;
+                }
+            }
+        }
+
+        let mut x = width / 2 + rng.range(-width / 8, width / 8 + 1);
+        let mut next_ford = rng.range(3, 7);
+        for y in 0..height {
+            let terrain = if y == next_ford {
+                next_ford += rng.range(6, 10);
+                Terrain::Ford
+            } else {
+                Terrain::Water
+            };
+            map.set(x, y, terrain);
+            map.set(x + 1, y, terrain);
+            x = (x + rng.range(-1, 2)).clamp(width / 4, width * 3 / 4);
+        }
+
+        for _ in 0..rng.range(3, 6) {
+            // Ruins shrink to fit maps smaller than them.
+            let (w, h) = (rng.range(3, 6).min(width), rng.range(3, 5).min(height));
+            let (x1, y1) = (
+                rng.range(0, (width - w).max(1)),
+                rng.range(0, (height - h).max(1)),
+            );
+            for y in y1..y1 + h {
+                for x in x1..x1 + w {
+                    let edge = x == x1 || x == x1 + w - 1 || y == y1 || y == y1 + h - 1;
+                    if edge && rng.roll_dice(1, 3) > 1 && map.get(x, y) != Terrain::Water {
+                        map.set(x, y, Terrain::Ruins);
+                    } else if map.get(x, y) == Terrain::Rock {
+                        map.set(x, y, Terrain::Open);
+                    }
+                }
+            }
+        }
+
+        for &p in zones {
+            map.clear(p, ZONE_RADIUS);
+        }
+        for &p in placements {
+            map.clear(p, PLACEMENT_RADIUS);
+        }
+        for pair in zones.windows(2) {
+            map.carve(pair[0], pair[1]);
+        }
+        map
+    }
+
+    /// Random fill smoothed into blobs: a tile ends up set if at least 5 of the 9 tiles around
+    /// it were set in the previous step.
+    fn automaton(&self, rng: &mut RandomNumberGenerator, chance: i32, steps: usize) -> Vec<bool> {
+        let mut set = self
+            .tiles
+            .iter()
+            .enumerate()
+            .map(|_| rng.roll_dice(1, 100) <= chance)
+            .collect::<Vec<_>>();
+        for _ in 0..steps {
+            let prev = set.clone();
+            for y in 0..self.height {
+                for x in 0..self.width {
+                    let n = (-1..=1)
+                        .flat_map(|dy| (-1..=1).map(move |dx| (x + dx, y + dy)))
+                        .filter(|&(nx, ny)| self.contains(nx, ny) && prev[self.index(nx, ny)])
+                        .count();
+                    set[self.index(x, y)] = n >= 5;
+                }
+            }
+        }
+        set
+    }
+
+    /// Opens up every tile around `p`, turning river into fords.
+    fn clear(&mut self, p: Point, radius: i32) {
+        for y in p.y - radius..=p.y + radius {
+            for x in p.x - radius..=p.x + radius {
+                self.open(x, y);
+            }
+        }
+    }
+
+    /// Opens an L-shaped corridor between two points.
+    fn carve(&mut self, from: Point, to: Point) {
+        for x in from.x.min(to.x)..=from.x.max(to.x) {
+            self.open(x, from.y);
+        }
+        for y in from.y.min(to.y)..=from.y.max(to.y) {
+            self.open(to.x, y);
+        }
+    }
+
+    fn open(&mut self, x: i32, y: i32) {
+        match self.get(x, y) {
+            Terrain::Water => self.set(x, y, Terrain::Ford),
+            Terrain::Rock => self.set(x, y, Terrain::Open),
+            _ => (),
+        }
+    }
+
+    pub fn width(&self) -> i32 {
+        self.width
+    }
+    pub fn height(&self) -> i32 {
+        self.height
+    }
+
+    fn contains(&self, x: i32, y: i32) -> bool {
+        x >= 0 && x < self.width && y >= 0 && y < self.height
+    }
+    fn index(&self, x: i32, y: i32) -> usize {
+        (y * self.width + x) as usize
+    }
+
+    pub fn set(&mut self, x: i32, y: i32, terrain: Terrain) {
+        if self.contains(x, y) {
+            let i = self.index(x, y);
+            self.tiles[i] = terrain;
+        }
+    }
+    /// Terrain at a tile. Anything off the map counts as open ground.
+    pub fn get(&self, x: i32, y: i32) -> Terrain {
+        if self.contains(x, y) {
+            self.tiles[self.index(x, y)]
+        } else {
+            Terrain::Open
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tiles(map: &Map) -> Vec<Terrain> {
+        (0..map.height())
+            .flat_map(|y| (0..map.width()).map(move |x| map.get(x, y)))
+            .collect()
+    }
+
+    #[test]
+    fn the_same_seed_gives_the_same_map() {
+        let zones = [Point::new(5, 5), Point::new(35, 15)];
+        let placements = [Point::new(20, 10)];
+        let a = Map::generate(42, 40, 20, &zones, &placements);
+        let b = Map::generate(42, 40, 20, &zones, &placements);
+        let c = Map::generate(43, 40, 20, &zones, &placements);
+
+        assert_eq!(tiles(&a), tiles(&b));
+        assert_ne!(tiles(&a), tiles(&c));
+    }
+
+    #[test]
+    fn tiny_maps_generate() {
+        for (width, height) in [(1, 1), (2, 8), (5, 4), (6, 5)] {
+            let map = Map::generate(7, width, height, &[Point::new(0, 0)], &[]);
+            assert_eq!(tiles(&map).len(), (width * height) as usize);
+        }
+    }
+}
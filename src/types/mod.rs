@@ -1,3 +1,31 @@
+mod ability;
+mod attack_shape;
+mod campaign;
+mod damage_type;
+mod death_effect;
+mod game_rules;
+mod layer;
+mod map;
+mod palette;
 mod race;
+mod scenario;
+mod status;
+mod structure_kind;
+mod terrain;
+mod unit_kind;
 
+pub use ability::{Ability, AbilityEffect, Targeting};
+pub use attack_shape::AttackShape;
+pub use campaign::{Campaign, CampaignProgress, Veteran};
+pub use damage_type::DamageType;
+pub use death_effect::DeathEffect;
+pub use game_rules::GameRules;
+pub use layer::{Layer, ALL_LAYERS, GROUND, GROUND_AIR};
+pub use map::Map;
+pub use palette::{brighten, Palette};
 pub use race::Race;
+pub use scenario::{Action, Objective, Region, Scenario, Trigger};
+pub use status::{Stacking, StatusEffect, StatusKind};
+pub use structure_kind::StructureKind;
+pub use terrain::Terrain;
+pub use unit_kind::UnitKind;
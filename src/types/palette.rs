@@ -0,0 +1,56 @@
+use bracket_lib::prelude::*;
+
+use serde::{Deserialize, Serialize};
+
+use crate::types::Race;
+
+/// The colors that tell the races apart. The colorblind themes are picked from the
+/// Okabe-Ito set so each pair of races stays distinct under that kind of color blindness.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Palette {
+    #[default]
+    Standard,
+    /// Safe for deuteranopia and protanopia.
+    RedGreen,
+    /// Safe for tritanopia.
+    BlueYellow,
+}
+
+impl Palette {
+    pub fn all() -> &'static [Palette] {
+        &[Palette::Standard, Palette::RedGreen, Palette::BlueYellow]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Palette::Standard => "Standard",
+            Palette::RedGreen => "Red-green safe",
+            Palette::BlueYellow => "Blue-yellow safe",
+        }
+    }
+
+    pub fn color(&self, race: Race) -> RGB {
+        match (self, race) {
+            (Palette::Standard, Race::Bug) => RGB::from_u8(170, 20, 0),
+            (Palette::Standard, Race::Human) => RGB::from_u8(175, 175, 175),
+            (Palette::Standard, Race::Bionic) => RGB::from_u8(0, 200, 0),
+            (Palette::RedGreen, Race::Bug) => RGB::from_u8(230, 159, 0),
+            (Palette::RedGreen, Race::Human) => RGB::from_u8(230, 230, 230),
+            (Palette::RedGreen, Race::Bionic) => RGB::from_u8(86, 180, 233),
+            (Palette::BlueYellow, Race::Bug) => RGB::from_u8(213, 94, 0),
+            (Palette::BlueYellow, Race::Human) => RGB::from_u8(230, 230, 230),
+            (Palette::BlueYellow, Race::Bionic) => RGB::from_u8(0, 158, 115),
+        }
+    }
+
+    /// A dim shade of the race's color, for tinting the tiles its units stand on.
+    pub fn background(&self, race: Race) -> RGB {
+        let color = self.color(race);
+        RGB::from_f32(color.r * 0.25, color.g * 0.25, color.b * 0.25)
+    }
+}
+
+/// The color of a hovered cell.
+pub fn brighten(color: RGB) -> RGB {
+    RGB::from_f32(color.r * 1.5, color.g * 1.5, color.b * 1.5)
+}
@@ -1,6 +1,19 @@
-#[derive(Copy, Clone, Debug, PartialEq)]
+use serde::{Deserialize, Serialize};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Race {
     Bug,
     Human,
     Bionic,
 }
+
+impl Race {
+    /// A shape for the race, so it can be told apart without color.
+    pub fn mark(&self) -> char {
+        match self {
+            Race::Bug => '▲',
+            Race::Human => '■',
+            Race::Bionic => '♦',
+        }
+    }
+}
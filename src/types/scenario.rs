@@ -0,0 +1,264 @@
+use std::{fs, io, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::types::{Race, StructureKind, Terrain, UnitKind};
+
+/// Where scenarios saved from the editor are kept.
+const SCENARIO_DIR: &str = "scenarios";
+/// Width and height of the world, in tiles, unless a scenario says otherwise.
+const DEFAULT_SIZE: (i32, i32) = (120, 60);
+
+fn default_size() -> (i32, i32) {
+    DEFAULT_SIZE
+}
+
+/// A rectangle of tiles, inclusive of both corners.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Region {
+    pub x1: i32,
+    pub y1: i32,
+    pub x2: i32,
+    pub y2: i32,
+}
+
+impl Region {
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        x >= self.x1 && x <= self.x2 && y >= self.y1 && y <= self.y2
+    }
+}
+
+/// A goal for one side. A side wins once all of its objectives are met at the same time.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Objective {
+    /// No enemy unit of this kind is left on the map.
+    Destroy(UnitKind),
+    /// Any of the side's units stands in the region.
+    Reach(Region),
+    /// The side still has units after this many rounds.
+    Survive(i32),
+    /// The side stands on every control point at the start of this many rounds in a row.
+    Hold(Vec<(i32, i32)>, i32),
+}
+
+impl Objective {
+    pub fn describe(&self) -> String {
+        match self {
+            Objective::Destroy(kind) => format!("destroy every enemy {}", kind.name()),
+            Objective::Reach(r) => format!("reach ({},{})-({},{})", r.x1, r.y1, r.x2, r.y2),
+            Objective::Survive(n) => format!("survive {} rounds", n),
+            Objective::Hold(points, n) => {
+                format!("hold {} control points for {} rounds", points.len(), n)
+            }
+        }
+    }
+}
+
+/// When a scenario trigger fires. Every trigger fires at most once.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Trigger {
+    /// At the start of the given round.
+    Round(i32),
+    /// When a unit of the side enters the region.
+    Enter(Race, Region),
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Action {
+    /// Reinforcements arrive at, or as close as possible to, the given tiles.
+    Spawn(Vec<(UnitKind, i32, i32)>),
+    /// Shows a message in the event log.
+    Message(String),
+}
+
+/// A map to play on, along with the goals and scripted events that make it a mission.
+/// Sides without objectives can only win by eliminating everyone else.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Scenario {
+    pub name: String,
+    /// Width and height of the world, in tiles.
+    #[serde(default = "default_size")]
+    pub size: (i32, i32),
+    /// The sides taking part, in turn order. Empty means all of them.
+    #[serde(default)]
+    pub players: Vec<Race>,
+    pub units: Vec<(UnitKind, i32, i32)>,
+    pub structures: Vec<(StructureKind, i32, i32)>,
+    pub nodes: Vec<(i32, i32)>,
+    /// Tiles painted over the open or generated map.
+    #[serde(default)]
+    pub terrain: Vec<(i32, i32, Terrain)>,
+    #[serde(default)]
+    pub objectives: Vec<(Race, Objective)>,
+    #[serde(default)]
+    pub triggers: Vec<(Trigger, Action)>,
+}
+
+const UNITS: &[(UnitKind, i32, i32)] = &[
+    (UnitKind::Spider, 10, 10),
+    (UnitKind::Spider, 11, 10),
+    (UnitKind::Queen, 8, 9),
+    (UnitKind::Jumper, 7, 10),
+    (UnitKind::Devourer, 6, 11),
+    (UnitKind::Trooper, 14, 13),
+    (UnitKind::Warden, 16, 13),
+    (UnitKind::Tank, 15, 12),
+    (UnitKind::Engineer, 13, 12),
+    (UnitKind::Vanguard, 20, 20),
+    (UnitKind::Sentry, 15, 20),
+    (UnitKind::Skirmisher, 13, 20),
+    (UnitKind::Assault, 13, 21),
+    (UnitKind::WarCarrier, 15, 22),
+];
+
+const BASES: &[(StructureKind, i32, i32)] = &[
+    (StructureKind::Hive, 4, 7),
+    (StructureKind::SporeTower, 9, 7),
+    (StructureKind::Barracks, 18, 10),
+    (StructureKind::Turret, 17, 14),
+    (StructureKind::Wall, 14, 15),
+    (StructureKind::Wall, 15, 15),
+    (StructureKind::Wall, 16, 15),
+    (StructureKind::Factory, 18, 23),
+    (StructureKind::BeamTower, 11, 22),
+];
+
+const NODES: &[(i32, i32)] = &[(12, 16), (30, 10), (30, 26), (45, 18)];
+
+const HIVE: Region = Region {
+    x1: 2,
+    y1: 5,
+    x2: 7,
+    y2: 10,
+};
+
+impl Scenario {
+    /// An open map with nothing on it, as a starting point in the editor.
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            size: DEFAULT_SIZE,
+            players: Vec::new(),
+            units: Vec::new(),
+            structures: Vec::new(),
+            nodes: Vec::new(),
+            terrain: Vec::new(),
+            objectives: Vec::new(),
+            triggers: Vec::new(),
+        }
+    }
+
+    /// The standard layout every built-in scenario starts from.
+    fn standard(name: &str) -> Self {
+        Self {
+            units: UNITS.to_vec(),
+            structures: BASES.to_vec(),
+            nodes: NODES.to_vec(),
+            ..Scenario::new(name)
+        }
+    }
+
+    /// The scenarios that ship with the game.
+    pub fn builtin() -> Vec<Scenario> {
+        vec![
+            Scenario::standard("Skirmish"),
+            Scenario {
+                objectives: vec![
+                    (Race::Human, Objective::Destroy(UnitKind::Queen)),
+                    (Race::Bug, Objective::Survive(8)),
+                ],
+                triggers: vec![
+                    (
+                        Trigger::Round(3),
+                        Action::Spawn(vec![(UnitKind::Spider, 6, 12), (UnitKind::Jumper, 7, 12)]),
+                    ),
+                    (
+                        Trigger::Round(3),
+                        Action::Message("Bug reinforcements hatch near the Hive".to_string()),
+                    ),
+                    (
+                        Trigger::Enter(Race::Human, HIVE),
+                        Action::Message("Humans have reached the Hive".to_string()),
+                    ),
+                ],
+                ..Scenario::standard("Queen Hunt")
+            },
+            Scenario {
+                objectives: vec![
+                    (Race::Bionic, Objective::Hold(vec![(30, 10), (30, 26)], 2)),
+                    (
+                        Race::Human,
+                        Objective::Reach(Region {
+                            x1: 17,
+                            y1: 22,
+                            x2: 20,
+                            y2: 25,
+                        }),
+                    ),
+                ],
+                triggers: vec![
+                    (
+                        Trigger::Round(4),
+                        Action::Spawn(vec![
+                            (UnitKind::Vanguard, 22, 24),
+                            (UnitKind::Skirmisher, 22, 25),
+                        ]),
+                    ),
+                    (
+                        Trigger::Round(4),
+                        Action::Message("Bionic reinforcements warp in by the Factory".to_string()),
+                    ),
+                ],
+                ..Scenario::standard("Hold the Line")
+            },
+        ]
+    }
+
+    /// The built-in scenarios followed by every readable one saved from the editor. Files that
+    /// fail to parse are reported on stderr and skipped.
+    pub fn load_all() -> Vec<Scenario> {
+        let mut scenarios = Scenario::builtin();
+        if let Ok(entries) = fs::read_dir(SCENARIO_DIR) {
+            let mut paths = entries
+                .filter_map(|e| e.ok().map(|e| e.path()))
+                .filter(|p| p.extension().is_some_and(|ext| ext == "ron"))
+                .collect::<Vec<_>>();
+            paths.sort();
+            scenarios.extend(paths.iter().filter_map(|p| {
+                let text = fs::read_to_string(p).ok()?;
+                ron::from_str(&text)
+                    .map_err(|e| eprintln!("Skipping scenario {}: {}", p.display(), e))
+                    .ok()
+            }));
+        }
+        scenarios
+    }
+
+    /// Writes the scenario to the scenarios directory, named after the scenario.
+    pub fn save(&self) -> io::Result<()> {
+        fs::create_dir_all(SCENARIO_DIR)?;
+        let file = self
+            .name
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() {
+                    c.to_ascii_lowercase()
+                } else {
+                    '_'
+                }
+            })
+            .collect::<String>();
+        let text = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(io::Error::other)?;
+        fs::write(Path::new(SCENARIO_DIR).join(file + ".ron"), text)
+    }
+
+    /// The sides taking part, in turn order.
+    pub fn players(&self) -> Vec<Race> {
+        if self.players.is_empty() {
+            vec![Race::Bug, Race::Human, Race::Bionic]
+        } else {
+            self.players.clone()
+        }
+    }
+}
@@ -0,0 +1,61 @@
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum StatusKind {
+    /// Loses 1 hp per stack at the start of each of its turns, ignoring shields and armor.
+    Poison,
+    /// Moves one tile less per move.
+    Slow,
+    /// Can neither move nor attack.
+    Stun,
+    /// Can't be targeted, but can't move or attack either.
+    Burrowed,
+    /// Takes 1 less damage from every hit.
+    Shielded,
+    /// Moves one tile further per move.
+    Haste,
+}
+
+/// How a status combines with one of the same kind already on a unit.
+pub enum Stacking {
+    /// Adds a stack up to the given maximum and refreshes the duration.
+    Intensity(i32),
+    /// Keeps a single stack with whichever duration is longer.
+    Refresh,
+}
+
+impl StatusKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            StatusKind::Poison => "Poison",
+            StatusKind::Slow => "Slow",
+            StatusKind::Stun => "Stun",
+            StatusKind::Burrowed => "Burrowed",
+            StatusKind::Shielded => "Shielded",
+            StatusKind::Haste => "Haste",
+        }
+    }
+
+    pub fn stacking(&self) -> Stacking {
+        match self {
+            StatusKind::Poison => Stacking::Intensity(3),
+            _ => Stacking::Refresh,
+        }
+    }
+}
+
+/// A status applied to a unit, lasting for a number of that unit's turns.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct StatusEffect {
+    pub kind: StatusKind,
+    pub duration: i32,
+    pub stacks: i32,
+}
+
+impl StatusEffect {
+    pub fn new(kind: StatusKind, duration: i32) -> Self {
+        Self {
+            kind,
+            duration,
+            stacks: 1,
+        }
+    }
+}
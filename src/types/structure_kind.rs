@@ -0,0 +1,166 @@
+use bracket_lib::prelude::*;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::{GameCell, Structure},
+    types::{DamageType, Palette, Race, StatusEffect, StatusKind, UnitKind, GROUND_AIR},
+};
+
+/// Every kind of building, along with its catalog data.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum StructureKind {
+    Hive,
+    SporeTower,
+    Barracks,
+    Turret,
+    Wall,
+    Factory,
+    BeamTower,
+}
+
+impl StructureKind {
+    /// Every structure kind in catalog order.
+    pub fn all() -> &'static [StructureKind] {
+        &[
+            StructureKind::Hive,
+            StructureKind::SporeTower,
+            StructureKind::Barracks,
+            StructureKind::Turret,
+            StructureKind::Wall,
+            StructureKind::Factory,
+            StructureKind::BeamTower,
+        ]
+    }
+
+    pub fn race(&self) -> Race {
+        match self {
+            StructureKind::Hive | StructureKind::SporeTower => Race::Bug,
+            StructureKind::Barracks | StructureKind::Turret | StructureKind::Wall => Race::Human,
+            StructureKind::Factory | StructureKind::BeamTower => Race::Bionic,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            StructureKind::Hive => "Hive",
+            StructureKind::SporeTower => "Spore Tower",
+            StructureKind::Barracks => "Barracks",
+            StructureKind::Turret => "Turret",
+            StructureKind::Wall => "Wall",
+            StructureKind::Factory => "Factory",
+            StructureKind::BeamTower => "Beam Tower",
+        }
+    }
+
+    pub fn symbol(&self) -> char {
+        match self {
+            StructureKind::Hive => '&',
+            StructureKind::SporeTower => 'o',
+            StructureKind::Barracks => '#',
+            StructureKind::Turret => '+',
+            StructureKind::Wall => '=',
+            StructureKind::Factory => '%',
+            StructureKind::BeamTower => '!',
+        }
+    }
+
+    /// Slot on a tileset's font sheet holding this structure's sprite.
+    pub fn tile(&self) -> FontCharType {
+        match self {
+            StructureKind::Hive => 32,
+            StructureKind::SporeTower => 33,
+            StructureKind::Barracks => 34,
+            StructureKind::Turret => 35,
+            StructureKind::Wall => 36,
+            StructureKind::Factory => 37,
+            StructureKind::BeamTower => 38,
+        }
+    }
+
+    /// Resources an Engineer needs to construct this structure.
+    pub fn cost(&self) -> i32 {
+        match self {
+            StructureKind::Wall => 1,
+            StructureKind::Turret | StructureKind::SporeTower | StructureKind::BeamTower => 3,
+            StructureKind::Barracks | StructureKind::Hive | StructureKind::Factory => 6,
+        }
+    }
+
+    /// How much of each damage type this structure takes, as a multiplier on the attacker's damage.
+    pub fn vulnerability(&self, damage_type: DamageType) -> f32 {
+        match damage_type {
+            DamageType::Melee => 0.5,
+            DamageType::Ranged => 1.0,
+            DamageType::Explosive => 2.0,
+        }
+    }
+    /// The damage a hit from an attacker would deal before armor.
+    pub fn damage_taken(&self, damage: i32, damage_type: DamageType) -> i32 {
+        (damage as f32 * self.vulnerability(damage_type)).round() as i32
+    }
+
+    /// The status this structure's attacks inflict on whatever they hit.
+    pub fn on_hit(&self) -> Option<StatusEffect> {
+        match self {
+            StructureKind::SporeTower => Some(StatusEffect::new(StatusKind::Poison, 2)),
+            _ => None,
+        }
+    }
+
+    /// The range and amount of hp this structure restores to friendly units at the start of its
+    /// side's turn.
+    pub fn heal_aura(&self) -> Option<(i32, i32)> {
+        match self {
+            StructureKind::Hive => Some((2, 1)),
+            StructureKind::Barracks => Some((1, 1)),
+            _ => None,
+        }
+    }
+
+    /// The kinds this structure can queue in Build mode.
+    pub fn produces(&self) -> &'static [UnitKind] {
+        match self {
+            StructureKind::Hive => &[UnitKind::Spider, UnitKind::Jumper, UnitKind::Queen],
+            StructureKind::Barracks => &[
+                UnitKind::Trooper,
+                UnitKind::Medic,
+                UnitKind::Warden,
+                UnitKind::Tank,
+                UnitKind::Engineer,
+            ],
+            StructureKind::Factory => &[
+                UnitKind::Vanguard,
+                UnitKind::Sentry,
+                UnitKind::Assault,
+                UnitKind::WarCarrier,
+            ],
+            _ => &[],
+        }
+    }
+
+    pub fn structure(&self) -> Structure {
+        match self {
+            StructureKind::Hive => Structure::new(*self, 10).with_footprint(2, 2).with_armor(1),
+            StructureKind::SporeTower => Structure::new(*self, 3)
+                .with_attack(1, 1, 3)
+                .with_targets(GROUND_AIR)
+                .with_damage_type(DamageType::Melee),
+            StructureKind::Barracks => Structure::new(*self, 10).with_footprint(2, 2).with_armor(1),
+            StructureKind::Turret => Structure::new(*self, 4)
+                .with_attack(1, 2, 3)
+                .with_targets(GROUND_AIR)
+                .with_armor(1),
+            StructureKind::Wall => Structure::new(*self, 5).with_armor(2),
+            StructureKind::Factory => Structure::new(*self, 10).with_footprint(2, 2).with_armor(1),
+            StructureKind::BeamTower => Structure::new(*self, 3).with_attack(2, 1, 4),
+        }
+    }
+
+    pub fn spawn(&self, x: i32, y: i32) -> (GameCell, Structure) {
+        (
+            GameCell::new(x, y, self.symbol(), Palette::default().color(self.race())),
+            self.structure(),
+        )
+    }
+}
@@ -0,0 +1,82 @@
+use bracket_lib::prelude::*;
+
+use serde::{Deserialize, Serialize};
+
+/// What a map tile is made of. Aircraft fly over all of it.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Terrain {
+    Open,
+    /// Cave wall, impassable on the ground.
+    Rock,
+    /// River water, impassable on the ground except at fords.
+    Water,
+    Ford,
+    Forest,
+    Ruins,
+}
+
+impl Terrain {
+    pub fn all() -> &'static [Terrain] {
+        &[
+            Terrain::Open,
+            Terrain::Rock,
+            Terrain::Water,
+            Terrain::Ford,
+            Terrain::Forest,
+            Terrain::Ruins,
+        ]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Terrain::Open => "Open ground",
+            Terrain::Rock => "Rock",
+            Terrain::Water => "River",
+            Terrain::Ford => "Ford",
+            Terrain::Forest => "Forest",
+            Terrain::Ruins => "Ruins",
+        }
+    }
+    pub fn symbol(&self) -> char {
+        match self {
+            Terrain::Open => '.',
+            Terrain::Rock => '▒',
+            Terrain::Water => '≈',
+            Terrain::Ford => ':',
+            Terrain::Forest => '♣',
+            Terrain::Ruins => '∩',
+        }
+    }
+    /// Slot on a tileset's font sheet holding this terrain's sprite.
+    pub fn tile(&self) -> FontCharType {
+        match self {
+            Terrain::Open => 48,
+            Terrain::Rock => 49,
+            Terrain::Water => 50,
+            Terrain::Ford => 51,
+            Terrain::Forest => 52,
+            Terrain::Ruins => 53,
+        }
+    }
+    pub fn color(&self) -> RGB {
+        match self {
+            Terrain::Open => RGB::from_u8(200, 200, 200),
+            Terrain::Rock => RGB::from_u8(110, 90, 70),
+            Terrain::Water => RGB::from_u8(40, 90, 200),
+            Terrain::Ford => RGB::from_u8(120, 150, 220),
+            Terrain::Forest => RGB::from_u8(30, 130, 30),
+            Terrain::Ruins => RGB::from_u8(150, 140, 120),
+        }
+    }
+
+    pub fn passable(&self) -> bool {
+        !matches!(self, Terrain::Rock | Terrain::Water)
+    }
+    /// Damage soaked up by the tile for a ground unit standing in it.
+    pub fn cover(&self) -> i32 {
+        match self {
+            Terrain::Forest | Terrain::Ruins => 1,
+            _ => 0,
+        }
+    }
+}
@@ -0,0 +1,434 @@
+use bracket_lib::prelude::*;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::{GameCell, Unit},
+    types::{
+        Ability, AbilityEffect, AttackShape, DamageType, DeathEffect, Layer, Palette, Race,
+        StatusEffect, StatusKind, StructureKind, Targeting, ALL_LAYERS, GROUND_AIR,
+    },
+};
+
+/// Every kind of unit that can appear on the map, along with its catalog data.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum UnitKind {
+    Larva,
+    Spider,
+    Queen,
+    Jumper,
+    Devourer,
+    Engineer,
+    Medic,
+    Trooper,
+    Warden,
+    Tank,
+    Vanguard,
+    Sentry,
+    Skirmisher,
+    Assault,
+    WarCarrier,
+    Interceptor,
+    Drone,
+}
+
+impl UnitKind {
+    /// Every unit kind in catalog order.
+    pub fn all() -> &'static [UnitKind] {
+        &[
+            UnitKind::Larva,
+            UnitKind::Spider,
+            UnitKind::Queen,
+            UnitKind::Jumper,
+            UnitKind::Devourer,
+            UnitKind::Engineer,
+            UnitKind::Medic,
+            UnitKind::Trooper,
+            UnitKind::Warden,
+            UnitKind::Tank,
+            UnitKind::Vanguard,
+            UnitKind::Sentry,
+            UnitKind::Skirmisher,
+            UnitKind::Assault,
+            UnitKind::WarCarrier,
+            UnitKind::Interceptor,
+            UnitKind::Drone,
+        ]
+    }
+
+    pub fn race(&self) -> Race {
+        match self {
+            UnitKind::Larva
+            | UnitKind::Spider
+            | UnitKind::Queen
+            | UnitKind::Jumper
+            | UnitKind::Devourer => Race::Bug,
+            UnitKind::Engineer
+            | UnitKind::Medic
+            | UnitKind::Trooper
+            | UnitKind::Warden
+            | UnitKind::Tank => Race::Human,
+            UnitKind::Vanguard
+            | UnitKind::Sentry
+            | UnitKind::Skirmisher
+            | UnitKind::Assault
+            | UnitKind::WarCarrier
+            | UnitKind::Interceptor
+            | UnitKind::Drone => Race::Bionic,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            UnitKind::Larva => "Larva",
+            UnitKind::Spider => "Spider",
+            UnitKind::Queen => "Queen",
+            UnitKind::Jumper => "Jumper",
+            UnitKind::Devourer => "Devourer",
+            UnitKind::Engineer => "Engineer",
+            UnitKind::Medic => "Medic",
+            UnitKind::Trooper => "Trooper",
+            UnitKind::Warden => "Warden",
+            UnitKind::Tank => "Tank",
+            UnitKind::Vanguard => "Vanguard",
+            UnitKind::Sentry => "Sentry",
+            UnitKind::Skirmisher => "Skirmisher",
+            UnitKind::Assault => "Assault",
+            UnitKind::WarCarrier => "War Carrier",
+            UnitKind::Interceptor => "Interceptor",
+            UnitKind::Drone => "Drone",
+        }
+    }
+
+    pub fn symbol(&self) -> char {
+        match self {
+            UnitKind::Larva => '~',
+            UnitKind::Spider => '*',
+            UnitKind::Queen => 'Q',
+            UnitKind::Jumper => 'J',
+            UnitKind::Devourer => 'D',
+            UnitKind::Engineer => 'E',
+            UnitKind::Medic => 'm',
+            UnitKind::Trooper => 't',
+            UnitKind::Warden => 'W',
+            UnitKind::Tank => 'T',
+            UnitKind::Vanguard => 'V',
+            UnitKind::Sentry => 'Y',
+            UnitKind::Skirmisher => 'X',
+            UnitKind::Assault => 'A',
+            UnitKind::WarCarrier => 'H',
+            UnitKind::Interceptor => '^',
+            UnitKind::Drone => 'd',
+        }
+    }
+
+    /// Slot on a tileset's font sheet holding this unit's sprite.
+    pub fn tile(&self) -> FontCharType {
+        match self {
+            UnitKind::Larva => 0,
+            UnitKind::Spider => 1,
+            UnitKind::Queen => 2,
+            UnitKind::Jumper => 3,
+            UnitKind::Devourer => 4,
+            UnitKind::Engineer => 5,
+            UnitKind::Medic => 6,
+            UnitKind::Trooper => 7,
+            UnitKind::Warden => 8,
+            UnitKind::Tank => 9,
+            UnitKind::Vanguard => 10,
+            UnitKind::Sentry => 11,
+            UnitKind::Skirmisher => 12,
+            UnitKind::Assault => 13,
+            UnitKind::WarCarrier => 14,
+            UnitKind::Interceptor => 15,
+            UnitKind::Drone => 16,
+        }
+    }
+
+    /// Resources needed to queue this unit for production.
+    pub fn cost(&self) -> i32 {
+        match self {
+            UnitKind::Spider | UnitKind::Trooper => 1,
+            UnitKind::Jumper | UnitKind::Engineer | UnitKind::Medic => 2,
+            UnitKind::Warden | UnitKind::Vanguard | UnitKind::Skirmisher => 3,
+            UnitKind::Devourer | UnitKind::Tank | UnitKind::Sentry => 4,
+            UnitKind::Assault => 5,
+            UnitKind::Queen | UnitKind::WarCarrier => 8,
+            UnitKind::Larva | UnitKind::Interceptor | UnitKind::Drone => 0,
+        }
+    }
+
+    /// How much of each damage type this unit takes, as a multiplier on the attacker's damage.
+    pub fn vulnerability(&self, damage_type: DamageType) -> f32 {
+        match (self.race(), self, damage_type) {
+            (Race::Bug, _, DamageType::Explosive) => 1.5,
+            (_, UnitKind::Tank, DamageType::Ranged) => 0.5,
+            (_, UnitKind::Tank, DamageType::Explosive) => 1.5,
+            (_, UnitKind::Warden, DamageType::Ranged) => 0.75,
+            (Race::Human, _, DamageType::Melee) => 1.5,
+            (_, UnitKind::Interceptor | UnitKind::Drone, DamageType::Explosive) => 2.0,
+            (_, UnitKind::Assault | UnitKind::WarCarrier, DamageType::Melee) => 0.5,
+            _ => 1.0,
+        }
+    }
+    /// The damage a hit from an attacker would deal before shields and armor.
+    pub fn damage_taken(&self, damage: i32, damage_type: DamageType) -> i32 {
+        (damage as f32 * self.vulnerability(damage_type)).round() as i32
+    }
+
+    /// The status this unit's attacks inflict on whatever they hit.
+    pub fn on_hit(&self) -> Option<StatusEffect> {
+        match self {
+            UnitKind::Jumper => Some(StatusEffect::new(StatusKind::Poison, 2)),
+            UnitKind::Tank => Some(StatusEffect::new(StatusKind::Slow, 2)),
+            UnitKind::Vanguard => Some(StatusEffect::new(StatusKind::Stun, 1)),
+            _ => None,
+        }
+    }
+
+    /// The abilities this unit can use in Ability mode.
+    pub fn abilities(&self) -> &'static [Ability] {
+        match self {
+            UnitKind::Spider | UnitKind::Jumper => &[Ability {
+                name: "Burrow",
+                effect: AbilityEffect::Burrow,
+                targeting: Targeting::Caster,
+                range: 0,
+                cooldown: 0,
+                charges: None,
+            }],
+            UnitKind::Queen => &[Ability {
+                name: "Brood",
+                effect: AbilityEffect::Spawn(UnitKind::Spider),
+                targeting: Targeting::Tile,
+                range: 1,
+                cooldown: 1,
+                charges: Some(3),
+            }],
+            UnitKind::Medic => &[Ability {
+                name: "Heal",
+                effect: AbilityEffect::Heal(2),
+                targeting: Targeting::Ally,
+                range: 1,
+                cooldown: 1,
+                charges: None,
+            }],
+            UnitKind::Engineer => &[Ability {
+                name: "Repair",
+                effect: AbilityEffect::Repair(2),
+                targeting: Targeting::Structure,
+                range: 1,
+                cooldown: 1,
+                charges: None,
+            }],
+            UnitKind::Trooper => &[Ability {
+                name: "Stimpack",
+                effect: AbilityEffect::Status(StatusEffect {
+                    kind: StatusKind::Haste,
+                    duration: 1,
+                    stacks: 1,
+                }),
+                targeting: Targeting::Caster,
+                range: 0,
+                cooldown: 2,
+                charges: None,
+            }],
+            UnitKind::Warden => &[Ability {
+                name: "Charge",
+                effect: AbilityEffect::Charge,
+                targeting: Targeting::Enemy,
+                range: 3,
+                cooldown: 2,
+                charges: None,
+            }],
+            UnitKind::Tank => &[Ability {
+                name: "Barrage",
+                effect: AbilityEffect::Blast(1),
+                targeting: Targeting::Area(1),
+                range: 5,
+                cooldown: 2,
+                charges: None,
+            }],
+            UnitKind::Sentry => &[Ability {
+                name: "EMP",
+                effect: AbilityEffect::Emp,
+                targeting: Targeting::Area(2),
+                range: 4,
+                cooldown: 3,
+                charges: Some(1),
+            }],
+            UnitKind::Assault => &[Ability {
+                name: "Barrier",
+                effect: AbilityEffect::Status(StatusEffect {
+                    kind: StatusKind::Shielded,
+                    duration: 2,
+                    stacks: 1,
+                }),
+                targeting: Targeting::Caster,
+                range: 0,
+                cooldown: 3,
+                charges: None,
+            }],
+            _ => &[],
+        }
+    }
+
+    /// The kinds this unit can queue in Build mode.
+    pub fn produces(&self) -> &'static [UnitKind] {
+        match self {
+            UnitKind::Larva => &[UnitKind::Spider, UnitKind::Jumper, UnitKind::Devourer],
+            UnitKind::WarCarrier => &[UnitKind::Vanguard, UnitKind::Skirmisher],
+            _ => &[],
+        }
+    }
+
+    /// Whether this unit turns into what it produces instead of spawning it alongside.
+    pub fn morphs(&self) -> bool {
+        matches!(self, UnitKind::Larva)
+    }
+
+    /// The structures this unit can construct in Build mode.
+    pub fn builds(&self) -> &'static [StructureKind] {
+        match self {
+            UnitKind::Engineer => &[
+                StructureKind::Wall,
+                StructureKind::Turret,
+                StructureKind::Barracks,
+            ],
+            _ => &[],
+        }
+    }
+
+    /// What this unit leaves behind when it dies.
+    pub fn on_death(&self) -> Option<DeathEffect> {
+        match self {
+            UnitKind::Queen => Some(DeathEffect::Burst(UnitKind::Spider, 3)),
+            UnitKind::Devourer => Some(DeathEffect::Burst(UnitKind::Spider, 2)),
+            UnitKind::Vanguard | UnitKind::Sentry | UnitKind::Skirmisher | UnitKind::Assault => {
+                Some(DeathEffect::Wreckage)
+            }
+            UnitKind::WarCarrier => Some(DeathEffect::ReleaseInterceptors),
+            _ => None,
+        }
+    }
+
+    /// The layer this unit moves on when it isn't burrowed.
+    pub fn layer(&self) -> Layer {
+        match self {
+            UnitKind::Interceptor | UnitKind::Drone => Layer::Air,
+            _ => Layer::Ground,
+        }
+    }
+
+    /// Whether this unit is small enough to ride in a transport.
+    pub fn infantry(&self) -> bool {
+        matches!(
+            self,
+            UnitKind::Spider
+                | UnitKind::Jumper
+                | UnitKind::Engineer
+                | UnitKind::Medic
+                | UnitKind::Trooper
+                | UnitKind::Warden
+                | UnitKind::Vanguard
+                | UnitKind::Sentry
+                | UnitKind::Skirmisher
+        )
+    }
+
+    /// What this unit deploys when clicking in Build mode.
+    pub fn launches(&self) -> Option<UnitKind> {
+        match self {
+            UnitKind::WarCarrier => Some(UnitKind::Interceptor),
+            UnitKind::Sentry => Some(UnitKind::Drone),
+            _ => None,
+        }
+    }
+
+    pub fn unit(&self) -> Unit {
+        match self {
+            UnitKind::Larva => Unit::new(*self, 1)
+                .with_move_dist(1)
+                .with_num_attacks(0)
+                .with_attack_range(0),
+            UnitKind::Spider => Unit::new_spider(),
+            UnitKind::Queen => Unit::new(*self, 2).with_move_dist(1).with_armor(1),
+            UnitKind::Jumper => Unit::new(*self, 1)
+                .with_num_moves(2)
+                .with_attack_range(1)
+                .with_damage_type(DamageType::Melee)
+                .with_targets(GROUND_AIR),
+            UnitKind::Devourer => Unit::new(*self, 3)
+                .with_damage(2)
+                .with_num_attacks(2)
+                .with_damage_type(DamageType::Melee)
+                .with_splash(AttackShape::Cone(2), 1, false)
+                .with_armor(1),
+            UnitKind::Engineer => Unit::new(*self, 1)
+                .with_move_dist(2)
+                .with_attack_range(1)
+                .with_damage_type(DamageType::Melee),
+            UnitKind::Medic => Unit::new(*self, 2)
+                .with_move_dist(2)
+                .with_num_attacks(0)
+                .with_attack_range(0),
+            UnitKind::Trooper => Unit::new(*self, 1)
+                .with_move_dist(1)
+                .with_targets(GROUND_AIR),
+            UnitKind::Warden => Unit::new(*self, 2)
+                .with_move_dist(1)
+                .with_num_attacks(3)
+                .with_attack_range(1)
+                .with_damage_type(DamageType::Melee)
+                .with_armor(1),
+            UnitKind::Tank => Unit::new(*self, 3)
+                .with_damage(2)
+                .with_damage_type(DamageType::Explosive)
+                .with_splash(AttackShape::Blast(1), 1, true)
+                .with_armor(2),
+            UnitKind::Vanguard => Unit::new(*self, 2)
+                .with_num_moves(2)
+                .with_num_attacks(2)
+                .with_attack_range(1)
+                .with_damage_type(DamageType::Melee)
+                .with_shields(1),
+            UnitKind::Sentry => Unit::new(*self, 3)
+                .with_num_attacks(2)
+                .with_targets(ALL_LAYERS)
+                .with_shields(1)
+                .with_interceptors(1),
+            UnitKind::Skirmisher => Unit::new(*self, 2)
+                .with_num_moves(2)
+                .with_num_attacks(2)
+                .with_targets(GROUND_AIR)
+                .with_shields(1),
+            UnitKind::Assault => Unit::new(*self, 4)
+                .with_num_attacks(4)
+                .with_damage_type(DamageType::Explosive)
+                .with_splash(AttackShape::Line(3), 0, false)
+                .with_shields(2)
+                .with_armor(1),
+            UnitKind::WarCarrier => Unit::new_war_carrier().with_capacity(2),
+            UnitKind::Interceptor => Unit::new(*self, 1)
+                .with_num_moves(2)
+                .with_num_attacks(2)
+                .with_attack_range(1)
+                .with_damage_type(DamageType::Melee)
+                .with_targets(GROUND_AIR)
+                .with_lifespan(2),
+            UnitKind::Drone => Unit::new(*self, 1)
+                .with_num_moves(2)
+                .with_attack_range(2)
+                .with_targets(GROUND_AIR)
+                .with_shields(1)
+                .with_lifespan(3),
+        }
+    }
+
+    pub fn spawn(&self, x: i32, y: i32) -> (GameCell, Unit) {
+        (
+            GameCell::new(x, y, self.symbol(), Palette::default().color(self.race())),
+            self.unit(),
+        )
+    }
+}